@@ -0,0 +1,222 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    fs
+};
+use clap::ArgEnum;
+use serde::Deserialize;
+use crate::{
+    error::KubeErr,
+    kube::{listener::Mode, port::PortArg},
+    registry::PortRange,
+    scenario::forward::ForwardArgs
+};
+
+// Constant
+const PROJECT_FILE: &str = ".kubeforward.toml";
+const USER_FILE: &str = ".config/kubeforward/config.toml";
+
+// Configuration file which declares a set of named profiles and the range of the auto ports
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    pub port_range: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>
+}
+
+// Profile describing a forward. The target is either a pod, a selector, a service or a workload
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub context: Option<String>,
+    pub namespace: Option<String>,
+    pub pod: Option<String>,
+    pub container: Option<String>,
+    pub selector: Option<String>,
+    pub service: Option<String>,
+    pub workload: Option<String>,
+    pub mode: Option<String>,
+    pub health: Option<String>,
+    #[serde(default)]
+    pub ports: Vec<String>
+}
+
+impl Config {
+    /// Load the configuration. The profiles and the port range of the user file (~/.config/kubeforward/config.toml)
+    /// are overridden by the ones of the closest project file (.kubeforward.toml)
+    pub fn load() -> Result<Config, KubeErr> {
+        let mut config = Config::default();
+        let user_file = dirs_next::home_dir().map(|home| home.join(USER_FILE));
+        let project_file = std::env::current_dir()
+            .ok()
+            .and_then(|dir| find_project_file(&dir));
+
+        for path in [user_file, project_file].into_iter().flatten() {
+            if path.is_file() {
+                let file = Config::from_file(&path)?;
+                config.profiles.extend(file.profiles);
+                config.port_range = file.port_range.or(config.port_range);
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Parse a configuration file
+    ///
+    /// # Arguments
+    /// * `path` - &Path
+    pub fn from_file(path: &Path) -> Result<Config, KubeErr> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| KubeErr::Config(path.display().to_string(), err.to_string()))?;
+
+        toml::from_str(&content)
+            .map_err(|err| KubeErr::Config(path.display().to_string(), err.to_string()))
+    }
+
+    /// Get a profile by its name
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `name` - &str
+    pub fn get_profile(&self, name: &str) -> Result<Profile, KubeErr> {
+        self.profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| KubeErr::MissingProfile(name.to_owned()))
+    }
+
+    /// Get the range used to assign the auto ports. The default range is used when none is declared
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn get_port_range(&self) -> Result<PortRange, KubeErr> {
+        match &self.port_range {
+            Some(range) => range.parse(),
+            None => Ok(PortRange::default())
+        }
+    }
+}
+
+impl Profile {
+    /// Convert the profile to the arguments used by the forward scenario
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn to_forward_args(&self) -> Result<ForwardArgs, KubeErr> {
+        let mode = match &self.mode {
+            Some(mode) => Some(Mode::from_str(mode, true).map_err(|_| KubeErr::WrongMode(mode.to_owned()))?),
+            None => None
+        };
+
+        let ports = self.ports
+            .iter()
+            .map(|port| port.parse::<PortArg>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let workload = match &self.workload {
+            Some(workload) => Some(workload.parse()?),
+            None => None
+        };
+
+        let args = ForwardArgs {
+            addresses: Vec::new(),
+            wait: None,
+            health: self.health.clone(),
+            mode,
+            ports,
+            pod: self.pod.clone(),
+            container: self.container.clone(),
+            selector: self.selector.clone(),
+            workload,
+            service: self.service.clone()
+        };
+
+        Ok(args)
+    }
+}
+
+/// Find the project file by walking up from the given directory
+///
+/// # Arguments
+/// * `dir` - &Path
+fn find_project_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kube::{port::RemotePort, workload::WorkloadKind};
+    use super::*;
+
+    const CONFIG: &str = r#"
+        port_range = "30000-30999"
+
+        [profiles.api]
+        context = "staging"
+        namespace = "backend"
+        workload = "deploy/api"
+        mode = "http"
+        ports = ["8080:3000", "9090"]
+
+        [profiles.db]
+        service = "postgres"
+        ports = ["5432"]
+    "#;
+
+    #[test]
+    fn expect_to_parse_profiles() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let profile = config.get_profile("api").unwrap();
+
+        assert_eq!(profile.context.as_deref(), Some("staging"));
+        assert_eq!(profile.namespace.as_deref(), Some("backend"));
+
+        let args = profile.to_forward_args().unwrap();
+        assert_eq!(args.mode, Some(Mode::Http));
+        assert_eq!(args.ports, vec![
+            PortArg { remote: RemotePort::Number(8080), local: Some(3000) },
+            PortArg { remote: RemotePort::Number(9090), local: None }
+        ]);
+        assert_eq!(args.workload.unwrap().kind, WorkloadKind::Deployment);
+    }
+
+    #[test]
+    fn expect_to_get_port_range() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+
+        assert_eq!(config.get_port_range().unwrap(), PortRange { start: 30000, end: 30999 });
+        assert_eq!(Config::default().get_port_range().unwrap(), PortRange::default());
+    }
+
+    #[test]
+    fn expect_to_not_get_unknown_profile() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+
+        assert!(config.get_profile("web").is_err());
+    }
+
+    #[test]
+    fn expect_to_not_convert_wrong_profile() {
+        let profile = Profile {
+            mode: Some("udp".to_owned()),
+            ..Default::default()
+        };
+
+        assert!(profile.to_forward_args().is_err());
+    }
+
+    #[test]
+    fn expect_to_find_project_file() {
+        let root = std::env::temp_dir().join(format!("kubeforward-config-test-{}", std::process::id()));
+        let nested = root.join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(PROJECT_FILE), CONFIG).unwrap();
+
+        assert_eq!(find_project_file(&nested).unwrap(), root.join(PROJECT_FILE));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    net::IpAddr,
+    os::unix::process::CommandExt,
+    process::{Command, Stdio},
+    time::Duration
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::UnixStream
+};
+use crate::{error::KubeErr, session::target::Target};
+use super::{get_socket_path, protocol::{Request, Response}};
+
+// Constant
+const DAEMON_COMMAND: &str = "daemon";
+const SPAWN_ATTEMPTS: usize = 20;
+const SPAWN_DELAY: Duration = Duration::from_millis(100);
+
+/// Send the targets to the daemon. The daemon is spawned when it's not running yet
+///
+/// # Arguments
+/// * `targets` - Vec<Target>
+/// * `addresses` - Vec<IpAddr>
+pub async fn start(targets: Vec<Target>, addresses: Vec<IpAddr>) -> Result<(), KubeErr> {
+    ensure_daemon().await?;
+
+    for target in targets {
+        let name = target.to_string();
+        let request = Request::Start { target: Box::new(target), addresses: addresses.clone() };
+        if let Response::Started { id } = send(request).await? {
+            println!("{id}\t{name}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the forwards of the daemon
+pub async fn list() -> Result<(), KubeErr> {
+    if let Response::Forwards { forwards } = send(Request::List).await? {
+        println!("ID\tSTATUS\tTARGET\tPORTS");
+        for forward in forwards {
+            println!("{forward}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Stop a forward of the daemon
+///
+/// # Arguments
+/// * `id` - u32
+pub async fn stop(id: u32) -> Result<(), KubeErr> {
+    if let Response::Stopped { id } = send(Request::Stop { id }).await? {
+        println!("{id}");
+    }
+
+    Ok(())
+}
+
+/// Print the logs of a forward of the daemon
+///
+/// # Arguments
+/// * `id` - u32
+pub async fn logs(id: u32) -> Result<(), KubeErr> {
+    if let Response::Logs { lines } = send(Request::Logs { id }).await? {
+        for line in lines {
+            println!("{line}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Stop the daemon. The forwards of the daemon are stopped along with it
+pub async fn shutdown() -> Result<(), KubeErr> {
+    if let Response::Shutdown = send(Request::Shutdown).await? {
+        log::info!("The daemon is stopped");
+    }
+
+    Ok(())
+}
+
+/// Send a request to the daemon and read its response
+///
+/// # Arguments
+/// * `request` - Request
+async fn send(request: Request) -> Result<Response, KubeErr> {
+    let path = get_socket_path()?;
+    let stream = UnixStream::connect(&path)
+        .await
+        .map_err(|_| KubeErr::DaemonNotRunning)?;
+
+    let (reader, mut writer) = stream.into_split();
+    let mut payload = serde_json::to_string(&request)
+        .map_err(|err| KubeErr::Daemon(err.to_string()))?;
+    payload.push('\n');
+    writer.write_all(payload.as_bytes()).await?;
+
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+
+    match serde_json::from_str(&line).map_err(|err| KubeErr::Daemon(err.to_string()))? {
+        Response::Error { message } => Err(KubeErr::Daemon(message)),
+        response => Ok(response)
+    }
+}
+
+/// Spawn the daemon in its own process group when it's not running. The daemon is therefore
+/// not stopped when the terminal is closed
+async fn ensure_daemon() -> Result<(), KubeErr> {
+    let path = get_socket_path()?;
+    if UnixStream::connect(&path).await.is_ok() {
+        return Ok(());
+    }
+
+    Command::new(std::env::current_exe()?)
+        .arg(DAEMON_COMMAND)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+
+    for _ in 0..SPAWN_ATTEMPTS {
+        tokio::time::sleep(SPAWN_DELAY).await;
+        if UnixStream::connect(&path).await.is_ok() {
+            log::info!("Started the daemon listening on {}", path.display());
+            return Ok(());
+        }
+    }
+
+    Err(KubeErr::DaemonNotRunning)
+}
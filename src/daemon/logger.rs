@@ -0,0 +1,158 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    future::Future,
+    sync::Mutex
+};
+use log::{Log, Metadata, Record, LevelFilter};
+use tokio::task::JoinHandle;
+use simple_logger::SimpleLogger;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use crate::events;
+
+// Constant
+const MAX_LINES: usize = 500;
+
+tokio::task_local! {
+    // Id of the forward which is running within the current task
+    pub static FORWARD_ID: u32;
+}
+
+// Lines logged by each forward of the daemon
+static LINES: Mutex<BTreeMap<u32, VecDeque<String>>> = Mutex::new(BTreeMap::new());
+
+// Logger of the daemon. Records are printed like the CLI unless the events are printed as JSON and
+// the records emitted by a forward are kept in memory so that they can be retrieved with the logs command
+struct DaemonLogger {
+    inner: SimpleLogger
+}
+
+impl Log for DaemonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        if !events::is_json_output() {
+            self.inner.log(record);
+        }
+
+        if let Ok(id) = FORWARD_ID.try_with(|id| *id) {
+            let timestamp = OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default();
+
+            push_line(id, format!("{timestamp} {:<5} {}", record.level(), record.args()));
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// Initialize the logger of the daemon
+pub fn init() -> Result<(), log::SetLoggerError> {
+    let inner = SimpleLogger::new().with_level(LevelFilter::Info);
+    log::set_max_level(LevelFilter::Info);
+    log::set_boxed_logger(Box::new(DaemonLogger { inner }))
+}
+
+/// Spawn a task which keeps the id of the forward of the current task, as the task-locals
+/// are not carried into the tasks spawned by tokio
+///
+/// # Arguments
+/// * `future` - F
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output> where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static
+{
+    match FORWARD_ID.try_with(|id| *id) {
+        Ok(id) => tokio::spawn(FORWARD_ID.scope(id, future)),
+        Err(_) => tokio::spawn(future)
+    }
+}
+
+/// Get the lines logged by a forward
+///
+/// # Arguments
+/// * `id` - u32
+pub fn get_lines(id: u32) -> Vec<String> {
+    LINES.lock()
+        .map(|lines| lines.get(&id).map(|l| l.iter().cloned().collect()).unwrap_or_default())
+        .unwrap_or_default()
+}
+
+/// Drop the lines logged by a forward
+///
+/// # Arguments
+/// * `id` - u32
+pub fn remove_lines(id: u32) {
+    if let Ok(mut lines) = LINES.lock() {
+        lines.remove(&id);
+    }
+}
+
+/// Keep a line logged by a forward. The oldest lines are dropped
+///
+/// # Arguments
+/// * `id` - u32
+/// * `line` - String
+fn push_line(id: u32, line: String) {
+    if let Ok(mut lines) = LINES.lock() {
+        let lines = lines.entry(id).or_default();
+        lines.push_back(line);
+        if lines.len() > MAX_LINES {
+            lines.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use log::Level;
+    use super::*;
+
+    #[test]
+    fn expect_to_keep_last_lines() {
+        for i in 0..MAX_LINES + 10 {
+            push_line(42, i.to_string());
+        }
+
+        let lines = get_lines(42);
+        assert_eq!(lines.len(), MAX_LINES);
+        assert_eq!(lines[0], "10");
+        assert!(get_lines(43).is_empty());
+    }
+
+    #[test]
+    fn expect_to_remove_lines() {
+        push_line(45, "started".to_owned());
+        remove_lines(45);
+
+        assert!(get_lines(45).is_empty());
+    }
+
+    #[tokio::test]
+    async fn expect_to_keep_lines_of_spawned_tasks() {
+        let logger = Arc::new(DaemonLogger { inner: SimpleLogger::new() });
+        let task_logger = logger.clone();
+
+        FORWARD_ID.scope(44, async move {
+            spawn(async move {
+                task_logger.log(&Record::builder()
+                    .level(Level::Info)
+                    .args(format_args!("from a spawned task"))
+                    .build());
+            }).await.unwrap();
+        }).await;
+
+        let lines = get_lines(44);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with("from a spawned task"));
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    path::PathBuf,
+    sync::Arc
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    signal::unix::{signal, SignalKind},
+    sync::Mutex,
+    task::JoinHandle
+};
+use tokio_util::sync::CancellationToken;
+use crate::{error::KubeErr, session::target::Target};
+use protocol::{Request, Response, Status, ForwardInfo};
+
+pub mod protocol;
+pub mod client;
+pub mod logger;
+
+// Constant
+const SOCKET_FILE: &str = "kubeforward/daemon.sock";
+
+// Forward running within the daemon
+struct Forward {
+    target: Target,
+    status: Arc<Mutex<Status>>,
+    handle: JoinHandle<()>
+}
+
+// Daemon which runs the forwards in the background. Forwards are managed through a Unix socket.
+// The daemon stops once the shutdown token is cancelled
+#[derive(Default)]
+struct Daemon {
+    forwards: Mutex<BTreeMap<u32, Forward>>,
+    next_id: Mutex<u32>,
+    shutdown: CancellationToken
+}
+
+impl Daemon {
+    /// Handle a request sent by the CLI
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `request` - Request
+    async fn handle(&self, request: Request) -> Response {
+        match request {
+            Request::Start { target, addresses } => {
+                let id = self.start(*target, addresses).await;
+                Response::Started { id }
+            },
+            Request::List => Response::Forwards { forwards: self.list().await },
+            Request::Stop { id } => match self.forwards.lock().await.remove(&id) {
+                Some(forward) => {
+                    forward.handle.abort();
+                    logger::remove_lines(id);
+                    log::info!("[{}] forward stopped by the user", forward.target);
+                    Response::Stopped { id }
+                },
+                None => Response::Error { message: KubeErr::UnknownForward(id).to_string() }
+            },
+            Request::Logs { id } => match self.forwards.lock().await.contains_key(&id) {
+                true => Response::Logs { lines: logger::get_lines(id) },
+                false => Response::Error { message: KubeErr::UnknownForward(id).to_string() }
+            },
+            Request::Shutdown => Response::Shutdown
+        }
+    }
+
+    /// Stop every forward of the daemon
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    async fn stop_all(&self) {
+        let mut forwards = self.forwards.lock().await;
+        for (id, forward) in std::mem::take(&mut *forwards) {
+            forward.handle.abort();
+            logger::remove_lines(id);
+        }
+    }
+
+    /// Start a forward in the background and return its id
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `target` - Target
+    /// * `addresses` - Vec<IpAddr>
+    async fn start(&self, target: Target, addresses: Vec<IpAddr>) -> u32 {
+        let mut next_id = self.next_id.lock().await;
+        *next_id += 1;
+        let id = *next_id;
+
+        let status = Arc::new(Mutex::new(Status::Running));
+        let task_status = status.clone();
+        let task_target = target.clone();
+
+        // logs emitted by the forward are kept under the id of the forward
+        let handle = tokio::spawn(logger::FORWARD_ID.scope(id, async move {
+            log::info!("[{task_target}] starting forward");
+            // the forward is stopped by aborting its task
+            let res = task_target.expose(&addresses, &task_target.new_stats(), CancellationToken::new()).await;
+            let status = match res {
+                Ok(_) => {
+                    log::info!("[{task_target}] forward stopped");
+                    Status::Stopped
+                },
+                Err(err) => {
+                    log::error!("[{task_target}] forward failed: {err}");
+                    Status::Failed(err.to_string())
+                }
+            };
+
+            *task_status.lock().await = status;
+        }));
+
+        self.forwards.lock().await.insert(id, Forward { target, status, handle });
+
+        id
+    }
+
+    /// List the forwards of the daemon
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    async fn list(&self) -> Vec<ForwardInfo> {
+        let forwards = self.forwards.lock().await;
+        let mut infos = Vec::new();
+        for (id, forward) in forwards.iter() {
+            infos.push(ForwardInfo {
+                id: *id,
+                target: forward.target.to_string(),
+                status: forward.status.lock().await.clone(),
+                mappings: forward.target.mappings.clone()
+            });
+        }
+
+        infos
+    }
+}
+
+/// Run the daemon until it receives a Ctrl-C or the shutdown command. The daemon ignores the SIGHUP
+/// signal in order to keep running when the terminal is closed
+pub async fn run() -> Result<(), KubeErr> {
+    let path = get_socket_path()?;
+    if path.exists() {
+        match UnixStream::connect(&path).await {
+            Ok(_) => return Err(KubeErr::DaemonRunning(path.display().to_string())),
+            Err(_) => std::fs::remove_file(&path)?
+        }
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    let daemon = Arc::new(Daemon::default());
+
+    log::info!("Daemon listening on {}", path.display());
+
+    loop {
+        tokio::select! {
+            res = listener.accept() => {
+                let (stream, _) = res?;
+                let daemon = daemon.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = handle_client(daemon, stream).await {
+                        log::error!("error with a client of the daemon: {err}");
+                    }
+                });
+            },
+            _ = hangup.recv() => log::info!("Ignoring the SIGHUP signal"),
+            _ = daemon.shutdown.cancelled() => {
+                log::warn!("Stopping the daemon as requested");
+                break;
+            },
+            _ = tokio::signal::ctrl_c() => {
+                log::warn!("Stopping the daemon");
+                break;
+            }
+        }
+    }
+
+    daemon.stop_all().await;
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+/// Read the request of a client and write the response
+///
+/// # Arguments
+/// * `daemon` - Arc<Daemon>
+/// * `stream` - UnixStream
+async fn handle_client(daemon: Arc<Daemon>, stream: UnixStream) -> Result<(), KubeErr> {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => daemon.handle(request).await,
+        Err(err) => Response::Error { message: err.to_string() }
+    };
+
+    let mut payload = serde_json::to_string(&response)
+        .map_err(|err| KubeErr::Daemon(err.to_string()))?;
+    payload.push('\n');
+    writer.write_all(payload.as_bytes()).await?;
+
+    // the daemon is stopped once the client got the response
+    if let Response::Shutdown = response {
+        daemon.shutdown.cancel();
+    }
+
+    Ok(())
+}
+
+/// Get the path of the control socket of the daemon
+pub fn get_socket_path() -> Result<PathBuf, KubeErr> {
+    dirs_next::runtime_dir()
+        .or_else(dirs_next::data_dir)
+        .map(|dir| dir.join(SOCKET_FILE))
+        .ok_or_else(|| KubeErr::Daemon("unable to find a directory for the control socket".to_owned()))
+}
@@ -0,0 +1,109 @@
+use std::{fmt, net::IpAddr};
+use serde::{Serialize, Deserialize};
+use crate::{kube::port::PortMapping, session::target::Target};
+
+// Request sent by the CLI to the daemon. A request is a single line of JSON
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    Start {
+        target: Box<Target>,
+        addresses: Vec<IpAddr>
+    },
+    List,
+    Stop {
+        id: u32
+    },
+    Logs {
+        id: u32
+    },
+    Shutdown
+}
+
+// Response sent back by the daemon. A response is a single line of JSON
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "response", rename_all = "lowercase")]
+pub enum Response {
+    Started {
+        id: u32
+    },
+    Forwards {
+        forwards: Vec<ForwardInfo>
+    },
+    Stopped {
+        id: u32
+    },
+    Logs {
+        lines: Vec<String>
+    },
+    Shutdown,
+    Error {
+        message: String
+    }
+}
+
+// Status of a forward managed by the daemon
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Running,
+    Stopped,
+    Failed(String)
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Running => write!(f, "running"),
+            Status::Stopped => write!(f, "stopped"),
+            Status::Failed(err) => write!(f, "failed: {err}")
+        }
+    }
+}
+
+// Summary of a forward managed by the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardInfo {
+    pub id: u32,
+    pub target: String,
+    pub status: Status,
+    pub mappings: Vec<PortMapping>
+}
+
+impl fmt::Display for ForwardInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ports: Vec<_> = self.mappings
+            .iter()
+            .map(|m| format!("{}:{} ({})", m.remote, m.local, m.mode))
+            .collect();
+
+        write!(f, "{}\t{}\t{}\t{}", self.id, self.status, self.target, ports.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kube::listener::Mode;
+    use super::*;
+
+    #[test]
+    fn expect_to_serialize_request() {
+        let request = serde_json::to_string(&Request::Stop { id: 2 }).unwrap();
+
+        assert_eq!(request, r#"{"command":"stop","id":2}"#);
+        assert!(matches!(serde_json::from_str(&request).unwrap(), Request::Stop { id: 2 }));
+        assert_eq!(serde_json::to_string(&Request::Shutdown).unwrap(), r#"{"command":"shutdown"}"#);
+    }
+
+    #[test]
+    fn expect_to_display_forward() {
+        let forward = ForwardInfo {
+            id: 1,
+            target: "current/default/api-1".to_owned(),
+            status: Status::Running,
+            mappings: vec![PortMapping { remote: 8080, local: 3000, mode: Mode::Http }]
+        };
+
+        assert_eq!(forward.to_string(), "1\trunning\tcurrent/default/api-1\t8080:3000 (http)");
+    }
+}
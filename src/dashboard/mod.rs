@@ -0,0 +1,333 @@
+use std::{
+    io::{self, Stdout},
+    net::IpAddr,
+    sync::Arc,
+    time::Duration
+};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen}
+};
+use tui::{backend::CrosstermBackend, Terminal, widgets::TableState};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use crate::{
+    error::KubeErr,
+    kube::stats::{Stats, Status},
+    env::EnvFile,
+    registry::{self, PortRange},
+    session::{Session, target::Target}
+};
+
+mod ui;
+
+// Constant
+const TICK: Duration = Duration::from_millis(250);
+
+// Action requested by the user which needs to leave the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Add,
+    Quit
+}
+
+// Forward displayed by the dashboard along with the statistics of each port mapping
+struct Forward {
+    target: Target,
+    stats: Vec<Arc<Stats>>,
+    handle: Option<JoinHandle<()>>
+}
+
+// Full screen dashboard which runs the targets of a session and displays their statistics
+pub struct Dashboard {
+    forwards: Vec<Forward>,
+    addresses: Arc<Vec<IpAddr>>,
+    port_range: PortRange,
+    env_file: Option<EnvFile>,
+    state: TableState
+}
+
+impl Dashboard {
+    /// Create a new Dashboard and start every target of the session
+    ///
+    /// # Arguments
+    /// * `session` - Session
+    pub fn new(session: Session) -> Dashboard {
+        let port_range = session.port_range();
+        let env_file = session.env_file();
+        let (targets, addresses) = session.into_parts();
+        let mut dashboard = Dashboard {
+            forwards: Vec::new(),
+            addresses: Arc::new(addresses),
+            port_range,
+            env_file,
+            state: TableState::default()
+        };
+
+        for target in targets {
+            dashboard.add(target);
+        }
+
+        dashboard
+    }
+
+    /// Add a target and start forwarding it. The auto ports of the target are assigned from the port range
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `target` - Target
+    pub fn add(&mut self, mut target: Target) -> &mut Self {
+        registry::assign_ports(&mut target, self.port_range, &self.addresses);
+        let stats = target.new_stats();
+        self.forwards.push(Forward { target, stats, handle: None });
+        self.start(self.forwards.len() - 1);
+
+        self
+    }
+
+    /// Display the dashboard until the user wants to add a forward or to quit. The forwards
+    /// keep running once the dashboard is left
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    pub async fn run(&mut self) -> Result<Action, KubeErr> {
+        // the logs would be printed over the dashboard. The level is restored as it may have been
+        // turned off by the JSON output
+        let level = log::max_level();
+        log::set_max_level(log::LevelFilter::Off);
+        let mut terminal = setup_terminal()?;
+        let res = self.draw_loop(&mut terminal).await;
+        let restored = restore_terminal(&mut terminal);
+        log::set_max_level(level);
+        restored?;
+
+        res
+    }
+
+    /// Stop every forward and remove the env file
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    pub fn stop_all(&mut self) {
+        for index in 0..self.forwards.len() {
+            self.stop(index);
+        }
+
+        if let Some(env_file) = &self.env_file {
+            env_file.remove();
+        }
+    }
+
+    /// Write the endpoints of the running forwards to the env file
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    fn update_env_file(&mut self) {
+        let env_file = match &mut self.env_file {
+            Some(env_file) => env_file,
+            None => return
+        };
+
+        let (targets, stats): (Vec<_>, Vec<_>) = self.forwards
+            .iter()
+            .map(|forward| (forward.target.clone(), forward.stats.clone()))
+            .unzip();
+
+        if let Err(err) = env_file.update(&targets, &stats, self.addresses[0]) {
+            log::warn!("{err}");
+        }
+    }
+
+    /// Draw the dashboard and handle the keys pressed by the user
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `terminal` - &mut Terminal<CrosstermBackend<Stdout>>
+    async fn draw_loop(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<Action, KubeErr> {
+        loop {
+            self.update_env_file();
+            let rows = self.rows();
+            terminal
+                .draw(|frame| ui::draw(frame, &rows, &mut self.state))
+                .map_err(to_err)?;
+
+            let key = tokio::task::spawn_blocking(read_key)
+                .await
+                .map_err(|err| KubeErr::Dashboard(err.to_string()))??;
+
+            // the Ctrl-C is received as a key as the terminal is in raw mode
+            if key.is_some_and(is_ctrl_c) {
+                return Ok(Action::Quit);
+            }
+
+            let selected = self.selected_forward();
+            match key.map(|k| k.code) {
+                Some(KeyCode::Char('q')) | Some(KeyCode::Esc) => return Ok(Action::Quit),
+                Some(KeyCode::Char('a')) => return Ok(Action::Add),
+                Some(KeyCode::Down) | Some(KeyCode::Char('j')) => self.select(rows.len(), 1),
+                Some(KeyCode::Up) | Some(KeyCode::Char('k')) => self.select(rows.len(), -1),
+                Some(KeyCode::Char('s')) => if let Some(index) = selected {
+                    self.stop(index);
+                },
+                Some(KeyCode::Char('r')) => if let Some(index) = selected {
+                    self.restart(index);
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Spawn the task forwarding the target. The status of the mappings is updated when the forward stops
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `index` - usize
+    fn start(&mut self, index: usize) {
+        let forward = &mut self.forwards[index];
+        let target = forward.target.clone();
+        let stats = forward.stats.clone();
+        let addresses = self.addresses.clone();
+
+        forward.handle = Some(tokio::spawn(async move {
+            // the forward is stopped by aborting its task
+            let (status, err) = match target.expose(&addresses, &stats, CancellationToken::new()).await {
+                Ok(_) => (Status::Stopped, None),
+                Err(err) => (Status::Failed, Some(err.to_string()))
+            };
+
+            for stat in &stats {
+                stat.set_status(status);
+                if let Some(err) = &err {
+                    stat.push_error(err.clone());
+                }
+            }
+        }));
+    }
+
+    /// Stop the forward of the target
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `index` - usize
+    fn stop(&mut self, index: usize) {
+        let forward = &mut self.forwards[index];
+        if let Some(handle) = forward.handle.take() {
+            handle.abort();
+            for stat in &forward.stats {
+                stat.set_status(Status::Stopped);
+            }
+        }
+    }
+
+    /// Restart the forward of the target with fresh statistics
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `index` - usize
+    fn restart(&mut self, index: usize) {
+        self.stop(index);
+        self.forwards[index].stats = self.forwards[index].target.new_stats();
+        self.start(index);
+    }
+
+    /// Move the selection of the table
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `len` - usize
+    /// * `offset` - isize
+    fn select(&mut self, len: usize, offset: isize) {
+        if len == 0 {
+            return;
+        }
+
+        let current = self.state.selected().unwrap_or_default() as isize;
+        let next = (current + offset).rem_euclid(len as isize) as usize;
+        self.state.select(Some(next));
+    }
+
+    /// Get the index of the forward of the selected row
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    fn selected_forward(&self) -> Option<usize> {
+        let rows = self.rows();
+        let selected = self.state.selected().unwrap_or_default();
+
+        rows.get(selected).map(|row| row.forward)
+    }
+
+    /// Build a row for each port mapping of the forwards
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    fn rows(&self) -> Vec<ui::Row> {
+        self.forwards
+            .iter()
+            .enumerate()
+            .flat_map(|(index, forward)| forward.target.mappings
+                .iter()
+                .zip(&forward.stats)
+                .map(move |(mapping, stats)| ui::Row {
+                    forward: index,
+                    target: forward.target.to_string(),
+                    container: forward.target.container.clone().unwrap_or_default(),
+                    mapping: *mapping,
+                    local_port: stats.local_port().unwrap_or(mapping.local),
+                    status: stats.status(),
+                    connections: stats.connections(),
+                    bytes: stats.bytes(),
+                    errors: stats.errors()
+                })
+            )
+            .collect()
+    }
+}
+
+/// Wait for a key pressed by the user during a tick
+fn read_key() -> Result<Option<KeyEvent>, KubeErr> {
+    if !event::poll(TICK).map_err(to_err)? {
+        return Ok(None);
+    }
+
+    match event::read().map_err(to_err)? {
+        Event::Key(key) => Ok(Some(key)),
+        _ => Ok(None)
+    }
+}
+
+/// Check whether the key is a Ctrl-C
+///
+/// # Arguments
+/// * `key` - KeyEvent
+fn is_ctrl_c(key: KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Switch the terminal to the alternate screen used by the dashboard
+fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>, KubeErr> {
+    enable_raw_mode().map_err(to_err)?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen).map_err(to_err)?;
+
+    Terminal::new(CrosstermBackend::new(stdout)).map_err(to_err)
+}
+
+/// Restore the terminal as it was before displaying the dashboard
+///
+/// # Arguments
+/// * `terminal` - &mut Terminal<CrosstermBackend<Stdout>>
+fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<(), KubeErr> {
+    disable_raw_mode().map_err(to_err)?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen).map_err(to_err)?;
+    terminal.show_cursor().map_err(to_err)
+}
+
+/// Convert an error of the terminal
+///
+/// # Arguments
+/// * `err` - io::Error
+fn to_err(err: io::Error) -> KubeErr {
+    KubeErr::Dashboard(err.to_string())
+}
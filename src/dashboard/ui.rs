@@ -0,0 +1,131 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row as TableRow, Table, TableState},
+    Frame
+};
+use crate::kube::{port::PortMapping, stats::Status};
+
+// Constant
+const TITLE: &str = " kubeforward ";
+const ERRORS_TITLE: &str = " Recent errors ";
+const HELP: &str = "↑/↓ select  s stop  r restart  a add  q quit";
+const HEADERS: [&str; 7] = ["TARGET", "CONTAINER", "PORTS", "MODE", "STATUS", "CONN", "BYTES"];
+const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+// Row of the dashboard which describes a port mapping of a forward
+pub struct Row {
+    pub forward: usize,
+    pub target: String,
+    pub container: String,
+    pub mapping: PortMapping,
+    pub local_port: u16,
+    pub status: Status,
+    pub connections: u64,
+    pub bytes: u64,
+    pub errors: Vec<String>
+}
+
+/// Draw the table of the forwards, the errors of the selected forward and the keybindings
+///
+/// # Arguments
+/// * `frame` - &mut Frame<B>
+/// * `rows` - &[Row]
+/// * `state` - &mut TableState
+pub fn draw<B: Backend>(frame: &mut Frame<B>, rows: &[Row], state: &mut TableState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(7), Constraint::Length(1)])
+        .split(frame.size());
+
+    let header = TableRow::new(HEADERS.iter().map(|h| Cell::from(*h)))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let table_rows = rows.iter().map(|row| TableRow::new(vec![
+        Cell::from(row.target.clone()),
+        Cell::from(row.container.clone()),
+        Cell::from(format!("{} → {}", row.mapping.remote, row.local_port)),
+        Cell::from(row.mapping.mode.to_string()),
+        Cell::from(row.status.to_string()).style(Style::default().fg(status_color(row.status))),
+        Cell::from(row.connections.to_string()),
+        Cell::from(format_bytes(row.bytes))
+    ]));
+
+    let widths = [
+        Constraint::Percentage(30),
+        Constraint::Percentage(14),
+        Constraint::Percentage(14),
+        Constraint::Percentage(8),
+        Constraint::Percentage(12),
+        Constraint::Percentage(8),
+        Constraint::Percentage(14)
+    ];
+
+    let table = Table::new(table_rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(TITLE))
+        .widths(&widths)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    if state.selected().is_none() && !rows.is_empty() {
+        state.select(Some(0));
+    }
+
+    frame.render_stateful_widget(table, chunks[0], state);
+
+    let errors = state.selected()
+        .and_then(|selected| rows.get(selected))
+        .map(|row| row.errors.join("\n"))
+        .unwrap_or_default();
+
+    let errors = Paragraph::new(errors)
+        .style(Style::default().fg(Color::Red))
+        .block(Block::default().borders(Borders::ALL).title(ERRORS_TITLE));
+
+    frame.render_widget(errors, chunks[1]);
+    frame.render_widget(Paragraph::new(HELP), chunks[2]);
+}
+
+/// Get the color used to display a status
+///
+/// # Arguments
+/// * `status` - Status
+fn status_color(status: Status) -> Color {
+    match status {
+        Status::Up => Color::Green,
+        Status::Connecting | Status::Reconnecting => Color::Yellow,
+        Status::Failed => Color::Red,
+        Status::Stopped => Color::DarkGray
+    }
+}
+
+/// Format a number of bytes with a binary unit (e.g: 1.5 KiB)
+///
+/// # Arguments
+/// * `bytes` - u64
+fn format_bytes(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expect_to_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration
+};
+use clap::ArgEnum;
+use crate::{
+    error::KubeErr,
+    kube::{listener::Mode, port::PortMapping, stats::{Stats, Status}},
+    session::target::Target
+};
+
+// Constant
+const WATCH_TICK: Duration = Duration::from_secs(1);
+const NAME_PLACEHOLDER: &str = "{name}";
+const NAMESPACE_PLACEHOLDER: &str = "{namespace}";
+const PORT_PLACEHOLDER: &str = "{port}";
+
+// Format of the file listing the forwarded endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum EnvFormat {
+    Dotenv,
+    Export,
+    Json
+}
+
+// File where the environment variables of the forwarded endpoints are written. The prefix of the
+// variables is built from the naming template (e.g: {namespace}_{name}_{port})
+#[derive(Debug, Clone)]
+pub struct EnvFile {
+    path: PathBuf,
+    format: EnvFormat,
+    naming: Option<String>,
+    content: Option<String>
+}
+
+impl EnvFile {
+    /// Create a new EnvFile
+    ///
+    /// # Arguments
+    /// * `path` - PathBuf
+    /// * `format` - EnvFormat
+    /// * `naming` - Option<String>
+    pub fn new(path: PathBuf, format: EnvFormat, naming: Option<String>) -> EnvFile {
+        EnvFile {
+            path,
+            format,
+            naming,
+            content: None
+        }
+    }
+
+    /// Get the naming template of the variables
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn naming(&self) -> Option<&str> {
+        self.naming.as_deref()
+    }
+
+    /// Write the variables of the endpoints which are listening. The file is only rewritten when
+    /// the variables have changed (e.g: a forward has been restarted on another port)
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `targets` - &[Target]
+    /// * `stats` - &[Vec<Arc<Stats>>]
+    /// * `address` - IpAddr
+    pub fn update(&mut self, targets: &[Target], stats: &[Vec<Arc<Stats>>], address: IpAddr) -> Result<(), KubeErr> {
+        let vars = get_env_vars(targets, stats, address, self.naming.as_deref());
+        let content = format_vars(&vars, self.format)?;
+        if self.content.as_ref() == Some(&content) {
+            return Ok(());
+        }
+
+        fs::write(&self.path, &content)
+            .map_err(|err| KubeErr::EnvFile(self.path.display().to_string(), err.to_string()))?;
+
+        log::info!("The forwarded endpoints have been written to {}", self.path.display());
+        self.content = Some(content);
+
+        Ok(())
+    }
+
+    /// Remove the file once the forwards are stopped
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn remove(&self) {
+        if self.path.is_file() {
+            if let Err(err) = fs::remove_file(&self.path) {
+                log::warn!("Unable to remove the file {}: {err}", self.path.display());
+            }
+        }
+    }
+}
+
+/// Rewrite the env file whenever the endpoints change until the task is aborted
+///
+/// # Arguments
+/// * `env_file` - EnvFile
+/// * `targets` - Vec<Target>
+/// * `stats` - Vec<Vec<Arc<Stats>>>
+/// * `address` - IpAddr
+pub async fn watch(mut env_file: EnvFile, targets: Vec<Target>, stats: Vec<Vec<Arc<Stats>>>, address: IpAddr) {
+    loop {
+        if let Err(err) = env_file.update(&targets, &stats, address) {
+            log::warn!("{err}");
+        }
+
+        tokio::time::sleep(WATCH_TICK).await;
+    }
+}
+
+/// Get the environment variables of each listening port mapping (e.g: API_HOST, API_PORT and API_URL).
+/// Without a naming template, the remote port is added to the name when the target forwards
+/// several ports (e.g: API_8080_URL). An unspecified address is replaced by the loopback address
+///
+/// # Arguments
+/// * `targets` - &[Target]
+/// * `stats` - &[Vec<Arc<Stats>>]
+/// * `address` - IpAddr
+/// * `naming` - Option<&str>
+pub fn get_env_vars(
+    targets: &[Target],
+    stats: &[Vec<Arc<Stats>>],
+    address: IpAddr,
+    naming: Option<&str>
+) -> Vec<(String, String)> {
+    let address = match address {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        address => address
+    };
+
+    let mut vars = Vec::new();
+    for (target, stats) in targets.iter().zip(stats) {
+        for (mapping, stats) in target.mappings.iter().zip(stats) {
+            let port = match (stats.local_port(), stats.status()) {
+                (Some(port), Status::Up | Status::Reconnecting) => port,
+                _ => continue
+            };
+
+            let prefix = get_prefix(target, mapping, naming);
+            let scheme = match mapping.mode {
+                Mode::Http => "http",
+                Mode::Tcp => "tcp"
+            };
+
+            vars.push((format!("{prefix}_HOST"), address.to_string()));
+            vars.push((format!("{prefix}_PORT"), port.to_string()));
+            vars.push((format!("{prefix}_URL"), format!("{scheme}://{}", SocketAddr::new(address, port))));
+        }
+    }
+
+    vars
+}
+
+/// Get the prefix of the environment variables of a port mapping. The placeholders of the naming
+/// template are replaced and the prefix is converted to an upper case identifier
+///
+/// # Arguments
+/// * `target` - &Target
+/// * `mapping` - &PortMapping
+/// * `naming` - Option<&str>
+fn get_prefix(target: &Target, mapping: &PortMapping, naming: Option<&str>) -> String {
+    let template = match (naming, target.mappings.len() > 1) {
+        (Some(naming), _) => naming.to_owned(),
+        (None, true) => format!("{NAME_PLACEHOLDER}_{PORT_PLACEHOLDER}"),
+        (None, false) => NAME_PLACEHOLDER.to_owned()
+    };
+
+    template
+        .replace(NAME_PLACEHOLDER, &target.name())
+        .replace(NAMESPACE_PLACEHOLDER, &target.namespace)
+        .replace(PORT_PLACEHOLDER, &mapping.remote.to_string())
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_'
+        })
+        .collect()
+}
+
+/// Format the variables with the given format
+///
+/// # Arguments
+/// * `vars` - &[(String, String)]
+/// * `format` - EnvFormat
+fn format_vars(vars: &[(String, String)], format: EnvFormat) -> Result<String, KubeErr> {
+    let content = match format {
+        EnvFormat::Dotenv => vars
+            .iter()
+            .map(|(name, value)| format!("{name}={value}\n"))
+            .collect(),
+        EnvFormat::Export => vars
+            .iter()
+            .map(|(name, value)| format!("export {name}=\"{value}\"\n"))
+            .collect(),
+        EnvFormat::Json => {
+            let vars: BTreeMap<_, _> = vars.iter().cloned().collect();
+            serde_json::to_string_pretty(&vars).map_err(|err| KubeErr::EnvFile("json".to_owned(), err.to_string()))?
+        }
+    };
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(mappings: Vec<PortMapping>) -> Target {
+        Target {
+            context: None,
+            namespace: "default".to_owned(),
+            pod: "api-1".to_owned(),
+            owner: None,
+            selector: Some("app=user-api".to_owned()),
+            workload_ref: None,
+            container: None,
+            mappings,
+            health: None
+        }
+    }
+
+    fn listening(target: &Target, ports: &[u16]) -> Vec<Vec<Arc<Stats>>> {
+        let stats = target.new_stats();
+        for (stats, port) in stats.iter().zip(ports) {
+            stats.set_local_port(*port);
+            stats.set_status(Status::Up);
+        }
+
+        vec![stats]
+    }
+
+    #[test]
+    fn expect_to_get_env_vars() {
+        let targets = vec![target(vec![PortMapping { remote: 8080, local: 23001, mode: Mode::Http }])];
+        let stats = listening(&targets[0], &[23001]);
+        let vars = get_env_vars(&targets, &stats, IpAddr::V4(Ipv4Addr::LOCALHOST), None);
+
+        assert_eq!(vars, vec![
+            ("USER_API_HOST".to_owned(), "127.0.0.1".to_owned()),
+            ("USER_API_PORT".to_owned(), "23001".to_owned()),
+            ("USER_API_URL".to_owned(), "http://127.0.0.1:23001".to_owned())
+        ]);
+    }
+
+    #[test]
+    fn expect_to_get_env_vars_with_remote_port() {
+        let targets = vec![target(vec![
+            PortMapping { remote: 8080, local: 0, mode: Mode::Http },
+            PortMapping { remote: 9090, local: 0, mode: Mode::Tcp }
+        ])];
+
+        let stats = listening(&targets[0], &[23001, 23002]);
+        let vars = get_env_vars(&targets, &stats, IpAddr::V4(Ipv4Addr::LOCALHOST), None);
+
+        assert_eq!(vars[5], ("USER_API_9090_URL".to_owned(), "tcp://127.0.0.1:23002".to_owned()));
+    }
+
+    #[test]
+    fn expect_to_skip_endpoints_not_listening() {
+        let targets = vec![target(vec![PortMapping { remote: 8080, local: 0, mode: Mode::Http }])];
+        let stats = vec![targets[0].new_stats()];
+
+        assert!(get_env_vars(&targets, &stats, IpAddr::V4(Ipv4Addr::LOCALHOST), None).is_empty());
+    }
+
+    #[test]
+    fn expect_to_get_env_vars_with_naming() {
+        let targets = vec![target(vec![PortMapping { remote: 8080, local: 0, mode: Mode::Http }])];
+        let stats = listening(&targets[0], &[23001]);
+        let vars = get_env_vars(&targets, &stats, IpAddr::V4(Ipv4Addr::LOCALHOST), Some("{namespace}-{name}"));
+
+        assert_eq!(vars[0].0, "DEFAULT_USER_API_HOST");
+    }
+
+    #[test]
+    fn expect_to_format_vars() {
+        let vars = vec![("API_PORT".to_owned(), "23001".to_owned())];
+
+        assert_eq!(format_vars(&vars, EnvFormat::Dotenv).unwrap(), "API_PORT=23001\n");
+        assert_eq!(format_vars(&vars, EnvFormat::Export).unwrap(), "export API_PORT=\"23001\"\n");
+        assert_eq!(format_vars(&vars, EnvFormat::Json).unwrap(), "{\n  \"API_PORT\": \"23001\"\n}");
+    }
+}
@@ -11,16 +11,103 @@ pub enum KubeErr {
     Kube(String),
     Prompt(String),
     EmptyPods(String),
+    PodNotFound(String, String),
     EmptyContainers,
     EmptyPorts,
     WrongPort,
     Network(String),
     SelectedPod,
-    ForwardPort
+    ForwardPort,
+    WrongAddress(String),
+    Bind(String, String),
+    WrongWorkload(String),
+    EmptySelector(String),
+    NoReadyPod(String),
+    EmptyServices(String),
+    WrongServicePort(u16),
+    EmptyWorkloads(String, String),
+    MissingArg(String),
+    Config(String, String),
+    MissingProfile(String),
+    WrongMode(String),
+    History(String),
+    EmptyHistory,
+    Daemon(String),
+    DaemonRunning(String),
+    DaemonNotRunning,
+    UnknownForward(u32),
+    Dashboard(String),
+    WrongPortRange(String),
+    Registry(String),
+    Exec(String, String),
+    EnvFile(String, String),
+    PodNotRunning(String, String),
+    WaitTimeout(String, u64),
+    StreamClosed(String),
+    Upstream(String),
+    ResponseTimeout(u64),
+    RemotePort(String, u16, String),
+    Probe(String),
+    UnknownPortName(String),
+    UnsupportedProtocol(u16, String)
 }
 
 impl std::error::Error for KubeErr {}
 
+impl KubeErr {
+    /// Get the kind of the error. The kind is a stable identifier used by the JSON output
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn kind(&self) -> &'static str {
+        match self {
+            KubeErr::Kubeconfig(..) => "kubeconfig",
+            KubeErr::Kube(..) => "kube",
+            KubeErr::Prompt(..) => "prompt",
+            KubeErr::EmptyPods(..) => "empty_pods",
+            KubeErr::PodNotFound(..) => "pod_not_found",
+            KubeErr::EmptyContainers => "empty_containers",
+            KubeErr::EmptyPorts => "empty_ports",
+            KubeErr::WrongPort => "wrong_port",
+            KubeErr::Network(..) => "network",
+            KubeErr::SelectedPod => "selected_pod",
+            KubeErr::ForwardPort => "forward_port",
+            KubeErr::WrongAddress(..) => "wrong_address",
+            KubeErr::Bind(..) => "bind",
+            KubeErr::WrongWorkload(..) => "wrong_workload",
+            KubeErr::EmptySelector(..) => "empty_selector",
+            KubeErr::NoReadyPod(..) => "no_ready_pod",
+            KubeErr::EmptyServices(..) => "empty_services",
+            KubeErr::WrongServicePort(..) => "wrong_service_port",
+            KubeErr::EmptyWorkloads(..) => "empty_workloads",
+            KubeErr::MissingArg(..) => "missing_arg",
+            KubeErr::Config(..) => "config",
+            KubeErr::MissingProfile(..) => "missing_profile",
+            KubeErr::WrongMode(..) => "wrong_mode",
+            KubeErr::History(..) => "history",
+            KubeErr::EmptyHistory => "empty_history",
+            KubeErr::Daemon(..) => "daemon",
+            KubeErr::DaemonRunning(..) => "daemon_running",
+            KubeErr::DaemonNotRunning => "daemon_not_running",
+            KubeErr::UnknownForward(..) => "unknown_forward",
+            KubeErr::Dashboard(..) => "dashboard",
+            KubeErr::WrongPortRange(..) => "wrong_port_range",
+            KubeErr::Registry(..) => "registry",
+            KubeErr::Exec(..) => "exec",
+            KubeErr::EnvFile(..) => "env_file",
+            KubeErr::PodNotRunning(..) => "pod_not_running",
+            KubeErr::WaitTimeout(..) => "wait_timeout",
+            KubeErr::StreamClosed(..) => "stream_closed",
+            KubeErr::Upstream(..) => "upstream",
+            KubeErr::ResponseTimeout(..) => "response_timeout",
+            KubeErr::RemotePort(..) => "remote_port",
+            KubeErr::Probe(..) => "probe",
+            KubeErr::UnknownPortName(..) => "unknown_port_name",
+            KubeErr::UnsupportedProtocol(..) => "unsupported_protocol"
+        }
+    }
+}
+
 impl std::fmt::Display for KubeErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -28,12 +115,45 @@ impl std::fmt::Display for KubeErr {
             KubeErr::Prompt(msg) => write!(f, "Something unexpected happened with the prompt: {msg}"),
             KubeErr::Kube(msg) => write!(f, "Error while querying with kubernetes {msg}"),
             KubeErr::EmptyPods(ns) => write!(f, "Could not found pod in the selected context and namespace: `{ns}`"),
+            KubeErr::PodNotFound(pod, ns) => write!(f, "The pod {pod} does not exist in the namespace: `{ns}`"),
             KubeErr::EmptyContainers => write!(f, "The pod does not contain any container !"),
             KubeErr::EmptyPorts => write!(f, "The selected container does not have any exposed ports"),
             KubeErr::WrongPort => write!(f, "The input port is not a numeric value"),
             KubeErr::Network(msg) => write!(f, "Error while sending / receiving data with pod {msg}"),
             KubeErr::SelectedPod => write!(f, "Unable to find selected pod"),
-            KubeErr::ForwardPort => write!(f, "Unable to forward port with the targeted pod")
+            KubeErr::ForwardPort => write!(f, "Unable to forward port with the targeted pod"),
+            KubeErr::WrongAddress(addr) => write!(f, "The address `{addr}` is not a valid IP address"),
+            KubeErr::Bind(addr, err) => write!(f, "Unable to listen on {addr}: {err}"),
+            KubeErr::WrongWorkload(workload) => write!(f, "The workload `{workload}` is not a valid reference such as deploy/name"),
+            KubeErr::EmptySelector(workload) => write!(f, "The workload `{workload}` does not have any label selector"),
+            KubeErr::NoReadyPod(selector) => write!(f, "Could not found a ready pod matching `{selector}`"),
+            KubeErr::EmptyServices(ns) => write!(f, "Could not found service in the selected context and namespace: `{ns}`"),
+            KubeErr::WrongServicePort(port) => write!(f, "The port {port} is not exposed by the service or its target port can't be found in the pod"),
+            KubeErr::EmptyWorkloads(kind, ns) => write!(f, "Could not found {kind} in the selected context and namespace: `{ns}`"),
+            KubeErr::MissingArg(arg) => write!(f, "The `{arg}` argument is required when the terminal is not interactive"),
+            KubeErr::Config(path, msg) => write!(f, "Unable to read the configuration file {path}: {msg}"),
+            KubeErr::MissingProfile(name) => write!(f, "The profile `{name}` is not declared in the configuration files"),
+            KubeErr::WrongMode(mode) => write!(f, "The mode `{mode}` is not supported, use http or tcp"),
+            KubeErr::History(msg) => write!(f, "Unable to read or write the history: {msg}"),
+            KubeErr::EmptyHistory => write!(f, "No forward has been saved in the history yet"),
+            KubeErr::Daemon(msg) => write!(f, "Error while communicating with the daemon: {msg}"),
+            KubeErr::DaemonRunning(path) => write!(f, "A daemon is already listening on {path}"),
+            KubeErr::DaemonNotRunning => write!(f, "The daemon is not running, start a forward with `kubeforward start`"),
+            KubeErr::UnknownForward(id) => write!(f, "The forward {id} does not exist"),
+            KubeErr::Dashboard(msg) => write!(f, "Unable to display the dashboard: {msg}"),
+            KubeErr::WrongPortRange(range) => write!(f, "The port range {range} is not valid (e.g: 20000-29999)"),
+            KubeErr::Registry(msg) => write!(f, "Unable to read or write the registry of the ports: {msg}"),
+            KubeErr::Exec(cmd, msg) => write!(f, "Unable to run the command {cmd}: {msg}"),
+            KubeErr::EnvFile(path, msg) => write!(f, "Unable to write the env file {path}: {msg}"),
+            KubeErr::PodNotRunning(pod, state) => write!(f, "The pod {pod} is not running ({state}), use --wait to wait until it's ready"),
+            KubeErr::WaitTimeout(target, secs) => write!(f, "No pod of {target} has been ready within {secs}s"),
+            KubeErr::StreamClosed(msg) => write!(f, "The stream with the pod has been closed: {msg}"),
+            KubeErr::Upstream(msg) => write!(f, "The request to the pod failed: {msg}"),
+            KubeErr::ResponseTimeout(secs) => write!(f, "The pod did not respond within {secs}s"),
+            KubeErr::RemotePort(pod, port, msg) => write!(f, "The pod {pod} failed to forward the port {port}: {msg}"),
+            KubeErr::Probe(msg) => write!(f, "The health request failed: {msg}"),
+            KubeErr::UnknownPortName(name) => write!(f, "No port named {name} is declared"),
+            KubeErr::UnsupportedProtocol(port, protocol) => write!(f, "The port {port} is using {protocol} but the port-forward only supports TCP")
         }
     }
 }
@@ -67,3 +187,9 @@ impl From<hyper::Error> for KubeErr {
         KubeErr::Network(err.to_string())
     }
 }
+
+impl From<std::io::Error> for KubeErr {
+    fn from(err: std::io::Error) -> Self {
+        KubeErr::Network(err.to_string())
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicBool, Ordering}
+};
+use serde::Serialize;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use crate::error::KubeErr;
+
+// Whether the events are printed as newline-delimited JSON
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+// Format used to output the progress of the forwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ArgEnum)]
+pub enum OutputFormat {
+    Text,
+    Json
+}
+
+// Error attached to an event. The kind is the variant of the KubeErr
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorInfo {
+    pub kind: &'static str,
+    pub message: String
+}
+
+impl From<&KubeErr> for ErrorInfo {
+    fn from(err: &KubeErr) -> Self {
+        ErrorInfo {
+            kind: err.kind(),
+            message: err.to_string()
+        }
+    }
+}
+
+// Event emitted during the lifetime of a forward
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    ForwardStarted {
+        pod: String,
+        port: u16,
+        address: SocketAddr
+    },
+    Probe {
+        pod: String,
+        port: u16,
+        ready: bool,
+        error: Option<ErrorInfo>
+    },
+    ConnectionOpened {
+        pod: String,
+        port: u16,
+        peer: SocketAddr
+    },
+    ConnectionClosed {
+        pod: String,
+        port: u16,
+        peer: SocketAddr,
+        bytes: Option<u64>,
+        error: Option<ErrorInfo>
+    },
+    ForwarderError {
+        pod: String,
+        port: u16,
+        error: ErrorInfo
+    },
+    RequestFailed {
+        pod: String,
+        port: u16,
+        status: u16,
+        error: ErrorInfo
+    },
+    Reconnecting {
+        pod: String,
+        port: u16,
+        attempt: u32,
+        delay_ms: u128,
+        error: ErrorInfo
+    },
+    Reconnected {
+        pod: String,
+        port: u16
+    },
+    PodChanged {
+        from: String,
+        to: String
+    },
+    ForwardFailed {
+        target: String,
+        error: ErrorInfo
+    },
+    Shutdown {
+        pod: String,
+        port: u16
+    },
+    Error {
+        error: ErrorInfo
+    }
+}
+
+// Event along with the time when it has been emitted
+#[derive(Serialize)]
+struct Envelope<'a> {
+    timestamp: String,
+    #[serde(flatten)]
+    event: &'a Event
+}
+
+/// Set the format used to output the events. The logs are disabled when the events are printed as JSON
+///
+/// # Arguments
+/// * `format` - OutputFormat
+pub fn set_output(format: OutputFormat) {
+    JSON_OUTPUT.store(format == OutputFormat::Json, Ordering::Relaxed);
+    if format == OutputFormat::Json {
+        log::set_max_level(log::LevelFilter::Off);
+    }
+}
+
+/// Check whether the events are printed as JSON
+pub fn is_json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Print the event as a line of JSON when the JSON output is used
+///
+/// # Arguments
+/// * `event` - Event
+pub fn emit(event: Event) {
+    if !is_json_output() {
+        return;
+    }
+
+    if let Some(line) = to_line(&event) {
+        println!("{line}");
+    }
+}
+
+/// Serialize the event with its timestamp
+///
+/// # Arguments
+/// * `event` - &Event
+fn to_line(event: &Event) -> Option<String> {
+    let envelope = Envelope {
+        timestamp: OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default(),
+        event
+    };
+
+    serde_json::to_string(&envelope).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expect_to_serialize_event() {
+        let event = Event::ForwarderError {
+            pod: "api-1".to_owned(),
+            port: 8080,
+            error: ErrorInfo::from(&KubeErr::ForwardPort)
+        };
+
+        let line = to_line(&event).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(value["event"], "forwarder_error");
+        assert_eq!(value["pod"], "api-1");
+        assert_eq!(value["error"]["kind"], "forward_port");
+        assert!(value["timestamp"].is_string());
+    }
+}
@@ -0,0 +1,178 @@
+use std::{os::unix::process::ExitStatusExt, sync::Arc, time::Duration};
+use futures::future::select_all;
+use tokio::{process::Command, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+use crate::{
+    env,
+    error::KubeErr,
+    kube::stats::{Stats, Status},
+    session::Session
+};
+
+// Constant
+const READY_TICK: Duration = Duration::from_millis(100);
+const INTERRUPTED_MSG: &str = "Interrupted while waiting for the forwards, the command is not run";
+// Exit code of a shell for a command killed by a signal is 128 plus the signal
+const SIGNAL_EXIT_CODE: i32 = 128;
+// Exit code when kubeforward fails before or after running the command, which is distinct from
+// the usual exit codes of a command (e.g: env, docker run)
+pub const FAILURE_EXIT_CODE: i32 = 125;
+
+/// Start the forwards of the session, wait until they're listening and run the command with
+/// environment variables pointing at the local addresses. The forwards are stopped once the command
+/// exits and the exit code of the command is returned. An error is returned when the forwards can't
+/// be started. The env file of the session is written while the command runs. The forwards are not
+/// stopped by a Ctrl-C which is left to the command
+///
+/// # Arguments
+/// * `session` - Session
+/// * `command` - Vec<String>
+pub async fn run(session: Session, command: Vec<String>) -> Result<i32, KubeErr> {
+    let mut env_file = session.env_file();
+    let (targets, addresses) = session.into_parts();
+    let addresses = Arc::new(addresses);
+    let stats: Vec<_> = targets
+        .iter()
+        .map(|target| target.new_stats())
+        .collect();
+
+    let shutdown = CancellationToken::new();
+    let mut handles: Vec<JoinHandle<Result<(), KubeErr>>> = targets
+        .iter()
+        .zip(&stats)
+        .map(|(target, stats)| {
+            let (target, stats, addresses, shutdown) = (target.clone(), stats.clone(), addresses.clone(), shutdown.clone());
+            tokio::spawn(async move { target.expose(&addresses, &stats, shutdown).await })
+        })
+        .collect();
+
+    // a Ctrl-C while waiting for the listeners stops the forwards as if the command was interrupted
+    let interrupted = tokio::select! {
+        res = wait_listeners(&mut handles, &stats) => res.map(|_| None),
+        _ = tokio::signal::ctrl_c() => Ok(Some(SIGNAL_EXIT_CODE + libc::SIGINT))
+    };
+
+    let res = match interrupted {
+        Ok(None) => {
+            let naming = env_file.as_ref().and_then(|file| file.naming());
+            let vars = env::get_env_vars(&targets, &stats, addresses[0], naming);
+            if let Some(Err(err)) = env_file.as_mut().map(|file| file.update(&targets, &stats, addresses[0])) {
+                log::warn!("{err}");
+            }
+
+            run_command(&command, vars).await
+        },
+        Ok(Some(code)) => {
+            log::warn!("{INTERRUPTED_MSG}");
+            Ok(code)
+        },
+        Err(err) => Err(err)
+    };
+
+    shutdown.cancel();
+    for handle in handles {
+        handle.abort();
+    }
+
+    if let Some(env_file) = env_file {
+        env_file.remove();
+    }
+
+    res
+}
+
+/// Wait until every listener is bound. An error is returned when a forward stops beforehand
+///
+/// # Arguments
+/// * `handles` - &mut [JoinHandle<Result<(), KubeErr>>]
+/// * `stats` - &[Vec<Arc<Stats>>]
+async fn wait_listeners(handles: &mut [JoinHandle<Result<(), KubeErr>>], stats: &[Vec<Arc<Stats>>]) -> Result<(), KubeErr> {
+    let ready = async {
+        while !stats.iter().flatten().all(|s| s.local_port().is_some() && s.status() == Status::Up) {
+            tokio::time::sleep(READY_TICK).await;
+        }
+    };
+
+    tokio::select! {
+        _ = ready => Ok(()),
+        (res, _, _) = select_all(handles.iter_mut()) => match res {
+            Ok(Err(err)) => Err(err),
+            Ok(Ok(_)) => Err(KubeErr::Network("the forward stopped before listening".to_owned())),
+            Err(err) => Err(KubeErr::Network(err.to_string()))
+        }
+    }
+}
+
+/// Run the command with the given environment variables and return its exit code
+///
+/// # Arguments
+/// * `command` - &[String]
+/// * `vars` - Vec<(String, String)>
+async fn run_command(command: &[String], vars: Vec<(String, String)>) -> Result<i32, KubeErr> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| KubeErr::MissingArg("<command>".to_owned()))?;
+
+    for (name, value) in &vars {
+        log::info!("{name}={value}");
+    }
+
+    // the command receives the ctrl-c of the terminal. The ctrl-c is ignored here so that kubeforward
+    // keeps the forwards until the command exits
+    let signals = tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {}
+    });
+
+    let status = Command::new(program)
+        .args(args)
+        .envs(vars)
+        .status()
+        .await;
+
+    signals.abort();
+
+    let status = status.map_err(|err| KubeErr::Exec(program.to_owned(), err.to_string()))?;
+    log::info!("The command {program} exited with {status}");
+
+    let code = status.code()
+        .or_else(|| status.signal().map(|signal| SIGNAL_EXIT_CODE + signal))
+        .unwrap_or(1);
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use crate::{
+        env::{EnvFile, EnvFormat},
+        kube::{listener::Mode, port::PortMapping},
+        registry::PortRange,
+        session::target::Target
+    };
+    use super::*;
+
+    #[tokio::test]
+    async fn expect_to_fail_when_the_forwards_fail_to_start() {
+        let mut session = Session::new(vec![IpAddr::V4(Ipv4Addr::LOCALHOST)], PortRange::default());
+        session.add_target(Target {
+            context: Some("kubeforward-missing-context".to_owned()),
+            namespace: "default".to_owned(),
+            pod: "api-1".to_owned(),
+            owner: None,
+            selector: None,
+            workload_ref: None,
+            container: None,
+            mappings: vec![PortMapping { remote: 8080, local: 28080, mode: Mode::Tcp }],
+            health: None
+        });
+
+        // the env file left by a previous session is removed
+        let path = std::env::temp_dir().join(format!("kubeforward-exec-test-{}.env", std::process::id()));
+        std::fs::write(&path, "API_PORT=28080").unwrap();
+        session.set_env_file(Some(EnvFile::new(path.clone(), EnvFormat::Dotenv, None)));
+
+        assert!(run(session, vec!["true".to_owned()]).await.is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,273 @@
+use std::{fmt, fs, path::PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::{
+    error::KubeErr,
+    kube::{CURRENT_CONTEXT, authenticate_with_cluster, pod::PodsList, workload::WorkloadRef},
+    session::target::Target
+};
+
+// Constant
+const HISTORY_FILE: &str = "kubeforward/history.json";
+const HISTORY_SIZE: usize = 50;
+
+// Forward which has been started previously. The target is saved as it is
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    #[serde(flatten)]
+    pub target: Target
+}
+
+impl From<&Target> for HistoryEntry {
+    fn from(target: &Target) -> Self {
+        HistoryEntry { target: target.clone() }
+    }
+}
+
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target = &self.target;
+        let context = target.context.as_deref().unwrap_or(CURRENT_CONTEXT);
+        let resource = target.selector.as_deref().unwrap_or(&target.pod);
+
+        let ports: Vec<_> = target.mappings
+            .iter()
+            .map(|m| format!("{}:{} ({})", m.remote, m.local, m.mode))
+            .collect();
+
+        write!(f, "{context}/{}/{resource}", target.namespace)?;
+        if let Some(container) = &target.container {
+            write!(f, " [{container}]")?;
+        }
+
+        write!(f, " {}", ports.join(", "))
+    }
+}
+
+impl HistoryEntry {
+    /// Convert the entry to a target. When the pod does not exist anymore, a ready pod of the same
+    /// workload or matching the same selector is used instead
+    ///
+    /// # Arguments
+    /// * `self` - Self
+    pub async fn into_target(self) -> Result<Target, KubeErr> {
+        let mut target = self.target.clone();
+        let mut pod_list = PodsList::new(target.context.clone(), &target.namespace, &target.selector).await?;
+        if pod_list.get_pod_name_list().contains(&target.pod) {
+            return Ok(target);
+        }
+
+        let pod = match (self.get_workload(), &target.selector, &target.owner) {
+            (Some(workload), _, _) => {
+                // the owner of the pod may have been replaced (e.g: the replicaset of a deployment on a rollout)
+                let client = authenticate_with_cluster(&target.context).await?;
+                let resolved = workload.resolve(client, &target.namespace).await?;
+                PodsList::new(target.context.clone(), &target.namespace, &Some(resolved.selector))
+                    .await?
+                    .filter_owned_by(&resolved.owners)
+                    .get_ready_pod_name()
+            },
+            (None, Some(_), _) => pod_list.get_ready_pod_name(),
+            (None, None, Some(owner)) => pod_list
+                .filter_owned_by(std::slice::from_ref(&owner.uid))
+                .get_ready_pod_name(),
+            (None, None, None) => None
+        };
+
+        let pod = pod.ok_or_else(|| KubeErr::NoReadyPod(self.to_string()))?;
+        log::info!("The pod {} does not exist anymore, using the pod {pod}", target.pod);
+        target.pod = pod;
+
+        Ok(target)
+    }
+
+    /// Get the workload of the pod. The workload is guessed from the owner of the pod for the entries
+    /// saved without the workload (e.g: deployment/api for the replicaset api-5d4f)
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    fn get_workload(&self) -> Option<WorkloadRef> {
+        match (&self.target.workload_ref, &self.target.owner) {
+            (Some(workload), _) => Some(workload.clone()),
+            (None, Some(_)) if self.target.selector.is_none() => self.target.workload().parse().ok(),
+            _ => None
+        }
+    }
+}
+
+// Store of the forwards which have been started, the most recent first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    entries: Vec<HistoryEntry>
+}
+
+impl History {
+    /// Load the history. An empty history is returned when nothing has been saved yet
+    pub fn load() -> Result<History, KubeErr> {
+        let path = get_history_path()?;
+        if !path.is_file() {
+            return Ok(History::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|err| KubeErr::History(err.to_string()))?;
+
+        serde_json::from_str(&content).map_err(|err| KubeErr::History(err.to_string()))
+    }
+
+    /// Save the history
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn save(&self) -> Result<(), KubeErr> {
+        let path = get_history_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| KubeErr::History(err.to_string()))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| KubeErr::History(err.to_string()))?;
+
+        fs::write(&path, content).map_err(|err| KubeErr::History(err.to_string()))
+    }
+
+    /// Add an entry at the top of the history. A previous identical entry is removed
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `entry` - HistoryEntry
+    pub fn push(&mut self, entry: HistoryEntry) -> &mut Self {
+        self.entries.retain(|e| *e != entry);
+        self.entries.insert(0, entry);
+        self.entries.truncate(HISTORY_SIZE);
+
+        self
+    }
+
+    /// Get the most recent entry
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn last(&self) -> Result<HistoryEntry, KubeErr> {
+        self.entries
+            .first()
+            .cloned()
+            .ok_or(KubeErr::EmptyHistory)
+    }
+
+    /// Get the entries of the history, the most recent first
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.entries.clone()
+    }
+}
+
+/// Save the target in the history. A failure does not prevent the forward from starting
+///
+/// # Arguments
+/// * `target` - &Target
+pub fn save_target(target: &Target) {
+    let res = History::load().and_then(|mut history| history
+        .push(HistoryEntry::from(target))
+        .save()
+    );
+
+    if let Err(err) = res {
+        log::warn!("Unable to save the forward in the history: {err}");
+    }
+}
+
+/// Get the path of the history file
+fn get_history_path() -> Result<PathBuf, KubeErr> {
+    dirs_next::data_dir()
+        .map(|dir| dir.join(HISTORY_FILE))
+        .ok_or_else(|| KubeErr::History("unable to find the data directory".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kube::{listener::Mode, pod::PodOwner, port::PortMapping};
+    use super::*;
+
+    fn entry(pod: &str) -> HistoryEntry {
+        HistoryEntry {
+            target: Target {
+                context: None,
+                namespace: "default".to_owned(),
+                pod: pod.to_owned(),
+                owner: Some(PodOwner {
+                    kind: "ReplicaSet".to_owned(),
+                    name: "api-5d4f".to_owned(),
+                    uid: "1234".to_owned()
+                }),
+                selector: None,
+                workload_ref: None,
+                container: Some("api".to_owned()),
+                mappings: vec![PortMapping { remote: 8080, local: 3000, mode: Mode::Http }],
+                health: None
+            }
+        }
+    }
+
+    #[test]
+    fn expect_to_push_entries() {
+        let mut history = History::default();
+        history
+            .push(entry("api-1"))
+            .push(entry("api-2"))
+            .push(entry("api-1"));
+
+        let pods: Vec<_> = history.entries()
+            .into_iter()
+            .map(|e| e.target.pod)
+            .collect();
+
+        assert_eq!(pods, vec!["api-1", "api-2"]);
+        assert_eq!(history.last().unwrap().target.pod, "api-1");
+    }
+
+    #[test]
+    fn expect_to_not_get_last_entry_of_empty_history() {
+        assert!(History::default().last().is_err());
+    }
+
+    #[test]
+    fn expect_to_display_entry() {
+        assert_eq!(entry("api-1").to_string(), "current/default/api-1 [api] 8080:3000 (http)");
+    }
+
+    #[test]
+    fn expect_to_serialize_entry() {
+        let mut history = History::default();
+        history.push(entry("api-1"));
+
+        let content = serde_json::to_string(&history).unwrap();
+        let parsed: History = serde_json::from_str(&content).unwrap();
+
+        assert_eq!(parsed.entries(), history.entries());
+    }
+
+    #[test]
+    fn expect_to_get_workload_of_entry() {
+        let mut entry = entry("api-1");
+
+        assert_eq!(entry.get_workload().unwrap().to_string(), "deployment/api");
+
+        entry.target.workload_ref = Some("sts/db".parse().unwrap());
+        assert_eq!(entry.get_workload().unwrap().to_string(), "statefulset/db");
+
+        entry.target.workload_ref = None;
+        entry.target.selector = Some("app=api".to_owned());
+        assert!(entry.get_workload().is_none());
+    }
+
+    #[test]
+    fn expect_to_parse_entry_without_workload() {
+        let content = r#"{"context":null,"namespace":"default","pod":"api-1","owner":null,"selector":null,"container":null,"mappings":[]}"#;
+        let entry: HistoryEntry = serde_json::from_str(content).unwrap();
+
+        assert_eq!(entry.target.pod, "api-1");
+        assert!(entry.target.workload_ref.is_none());
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+// Constant
+const INITIAL_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(8);
+pub const MAX_ATTEMPTS: u32 = 6;
+
+// Exponential backoff used to retry to open a port-forward stream with the pod
+#[derive(Debug)]
+pub struct Backoff {
+    delay: Duration,
+    attempt: u32
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            delay: INITIAL_DELAY,
+            attempt: 0
+        }
+    }
+}
+
+impl Backoff {
+    /// Get the delay to wait before the next attempt. None is returned when there are no more attempts left
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.attempt >= MAX_ATTEMPTS {
+            return None;
+        }
+
+        let delay = self.delay;
+        self.delay = std::cmp::min(self.delay * 2, MAX_DELAY);
+        self.attempt += 1;
+
+        Some(delay)
+    }
+
+    /// Get the number of attempts which has been made
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expect_to_double_delay_until_max() {
+        let mut backoff = Backoff::default();
+        let delays: Vec<_> = std::iter::from_fn(|| backoff.next_delay()).collect();
+
+        assert_eq!(delays.len() as u32, MAX_ATTEMPTS);
+        assert_eq!(*delays.first().unwrap(), INITIAL_DELAY);
+        assert_eq!(*delays.get(1).unwrap(), INITIAL_DELAY * 2);
+        assert_eq!(*delays.last().unwrap(), MAX_DELAY);
+    }
+
+    #[test]
+    fn expect_to_count_attempts() {
+        let mut backoff = Backoff::default();
+        backoff.next_delay();
+        backoff.next_delay();
+
+        assert_eq!(backoff.attempt(), 2);
+    }
+}
@@ -1,4 +1,12 @@
-use k8s_openapi::api::core::v1::Container;
+use k8s_openapi::{api::core::v1::Container, apimachinery::pkg::util::intstr::IntOrString};
+use super::{
+    listener::Mode,
+    port::{guess_mode, DeclaredPort, PortSource, SuggestedPort}
+};
+
+// Constant
+const PORT_ENV: &str = "PORT";
+const PORT_ENV_SUFFIX: &str = "_PORT";
 
 // Struct used to improve works on container
 #[derive(Debug, Default)]
@@ -37,16 +45,16 @@ impl ContainerWrapper {
         self
     }
 
-    /// Retrieve a list of port for given saved container
+    /// Retrieve a list of port for given saved container along with their name and their protocol
     ///
     /// # Arguments
     /// * `&mut self` - Self
-    pub fn get_port_for_container(&mut self) -> Option<Vec<i32>> {
+    pub fn get_port_for_container(&mut self) -> Option<Vec<DeclaredPort>> {
         if let Some(container) = self.container.clone() {
             if let Some(ports) = container.ports {
                 let port_list = ports
                     .into_iter()
-                    .map(|p| p.container_port)
+                    .map(|p| DeclaredPort::new(p.container_port, p.name, p.protocol))
                     .collect::<Vec<_>>();
 
                 return Some(port_list);
@@ -56,6 +64,68 @@ impl ContainerWrapper {
         None
     }
 
+    /// Get the ports of the saved container which are not declared but are used by its probes (liveness,
+    /// readiness and startup) or set in its environment variables (e.g: PORT, METRICS_PORT)
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn get_suggested_ports(&self) -> Vec<SuggestedPort> {
+        let container = match &self.container {
+            Some(container) => container,
+            None => return Vec::new()
+        };
+
+        let declared: Vec<_> = container.ports
+            .iter()
+            .flatten()
+            .map(|p| p.container_port)
+            .collect();
+
+        let probes = [&container.liveness_probe, &container.readiness_probe, &container.startup_probe]
+            .into_iter()
+            .flatten()
+            .flat_map(|probe| [
+                probe.http_get.as_ref().map(|action| &action.port),
+                probe.tcp_socket.as_ref().map(|action| &action.port)
+            ])
+            .flatten()
+            .filter_map(|port| match port {
+                IntOrString::Int(port) => Some(SuggestedPort { port: *port, source: PortSource::Probe }),
+                IntOrString::String(_) => None
+            });
+
+        let env = container.env
+            .iter()
+            .flatten()
+            .filter(|var| var.name == PORT_ENV || var.name.ends_with(PORT_ENV_SUFFIX))
+            .filter_map(|var| var.value.as_ref()?.trim().parse::<i32>().ok())
+            .map(|port| SuggestedPort { port, source: PortSource::Env });
+
+        let mut suggested: Vec<SuggestedPort> = Vec::new();
+        for port in probes.chain(env) {
+            let valid = port.port > 0 && port.port <= u16::MAX as i32;
+            if valid && !declared.contains(&port.port) && !suggested.iter().any(|s| s.port == port.port) {
+                suggested.push(port);
+            }
+        }
+
+        suggested
+    }
+
+    /// Guess the mode to use for the given port of the saved container
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `port` - i32
+    pub fn get_mode_for_port(&self, port: i32) -> Mode {
+        let container_port = self.container
+            .as_ref()
+            .and_then(|c| c.ports.as_ref())
+            .and_then(|ports| ports.iter().find(|p| p.container_port == port));
+
+        guess_mode(container_port.and_then(|p| p.name.as_deref()), port)
+    }
+
     /// Get a list of containers name
     ///
     /// # Arguments
@@ -71,7 +141,7 @@ impl ContainerWrapper {
 
 #[cfg(test)]
 mod tests {
-    use k8s_openapi::api::core::v1::ContainerPort;
+    use k8s_openapi::api::core::v1::{ContainerPort, EnvVar, HTTPGetAction, Probe, TCPSocketAction};
 
     use super::*;
 
@@ -112,6 +182,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn expect_to_get_containers_name() {
         let containers = setup();
         let names = ContainerWrapper::new(containers).get_containers_name();
@@ -127,24 +198,47 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn expect_to_get_port_for_one_exposed_port() {
         let containers = setup();
         let ports = ContainerWrapper::new(containers)
             .set_selected_container("foo".to_owned())
             .get_port_for_container().unwrap();
 
-        assert_eq!(*ports.get(0).unwrap(), 3000);
+        assert_eq!(ports.get(0).unwrap().port, 3000);
+        assert_eq!(ports.get(0).unwrap().to_string(), "3000/TCP (Http)");
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn expect_to_get_port_for_multiple_exposed_ports() {
         let containers = setup();
         let ports = ContainerWrapper::new(containers)
             .set_selected_container("bar".to_owned())
             .get_port_for_container().unwrap();
 
-        assert_eq!(*ports.get(0).unwrap(), 443);
-        assert_eq!(*ports.get(1).unwrap(), 80);
+        assert_eq!(ports.get(0).unwrap().port, 443);
+        assert_eq!(ports.get(1).unwrap().port, 80);
+    }
+
+    #[test]
+    fn expect_to_get_http_mode_for_http_port() {
+        let containers = setup();
+        let mode = ContainerWrapper::new(containers)
+            .set_selected_container("foo".to_owned())
+            .get_mode_for_port(3000);
+
+        assert_eq!(mode, Mode::Http);
+    }
+
+    #[test]
+    fn expect_to_get_tcp_mode_for_non_http_port() {
+        let containers = setup();
+        let mode = ContainerWrapper::new(containers)
+            .set_selected_container("bar".to_owned())
+            .get_mode_for_port(443);
+
+        assert_eq!(mode, Mode::Tcp);
     }
 
     #[test]
@@ -156,4 +250,38 @@ mod tests {
 
         assert!(ports.is_none());
     }
+
+    #[test]
+    fn expect_to_get_suggested_ports() {
+        let mut containers = setup();
+        containers[0].liveness_probe = Some(Probe {
+            http_get: Some(HTTPGetAction {
+                port: IntOrString::Int(6060),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        containers[0].readiness_probe = Some(Probe {
+            tcp_socket: Some(TCPSocketAction {
+                port: IntOrString::Int(3000),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        containers[0].env = Some(vec![
+            EnvVar { name: "METRICS_PORT".to_owned(), value: Some("9090".to_owned()), ..Default::default() },
+            EnvVar { name: "PORT".to_owned(), value: Some("6060".to_owned()), ..Default::default() },
+            EnvVar { name: "DB_HOST".to_owned(), value: Some("5432".to_owned()), ..Default::default() }
+        ]);
+
+        let mut wrapper = ContainerWrapper::new(containers);
+        let ports = wrapper
+            .set_selected_container("foo".to_owned())
+            .get_suggested_ports();
+
+        assert_eq!(ports, vec![
+            SuggestedPort { port: 6060, source: PortSource::Probe },
+            SuggestedPort { port: 9090, source: PortSource::Env }
+        ]);
+    }
 }
@@ -0,0 +1,284 @@
+use std::{collections::{BTreeMap, BTreeSet}, time::Duration};
+use futures::StreamExt;
+use kube::{
+    api::{Api, ListParams},
+    runtime::watcher::{watcher, Event},
+    Client,
+    ResourceExt
+};
+use k8s_openapi::{api::core::v1::Pod, apimachinery::pkg::apis::meta::v1::OwnerReference};
+use tokio::sync::watch::Sender;
+use super::{
+    pod::is_pod_ready,
+    workload::{REPLICA_SET, WorkloadKind, WorkloadRef, is_owned_by}
+};
+use crate::{events, error::KubeErr};
+
+// Constant
+const WATCHER_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// Filter keeping the pods owned by the workload which is followed. The replicasets of a deployment
+// are resolved again when a pod owned by an unknown replicaset shows up (e.g: on a rollout). The
+// replicasets which don't belong to the deployment are kept to not resolve them on every event
+pub struct OwnerFilter {
+    client: Client,
+    namespace: String,
+    workload: WorkloadRef,
+    owners: Vec<String>,
+    rejected: BTreeSet<String>
+}
+
+impl OwnerFilter {
+    /// Create a new OwnerFilter. The owners are resolved once the pods are watched
+    ///
+    /// # Arguments
+    /// * `client` - Client
+    /// * `namespace` - &str
+    /// * `workload` - WorkloadRef
+    pub fn new(client: Client, namespace: &str, workload: WorkloadRef) -> OwnerFilter {
+        OwnerFilter {
+            client,
+            namespace: namespace.to_owned(),
+            workload,
+            owners: Vec::new(),
+            rejected: BTreeSet::new()
+        }
+    }
+
+    /// Resolve the owners of the pods of the workload. The previous owners are kept on error
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    async fn resolve(&mut self) {
+        match self.workload.resolve(self.client.clone(), &self.namespace).await {
+            Ok(resolved) => self.owners = resolved.owners,
+            Err(err) => log::warn!("Unable to resolve the owners of the pods of the {}: {err}", self.workload)
+        }
+    }
+
+    /// Check whether the pod is owned by the workload
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `pod` - &Pod
+    async fn accept(&mut self, pod: &Pod) -> bool {
+        if is_owned_by(pod.owner_references(), &self.owners) {
+            return true;
+        }
+
+        if self.workload.kind != WorkloadKind::Deployment {
+            return false;
+        }
+
+        let unknown = get_unknown_replica_sets(pod.owner_references(), &self.owners, &self.rejected);
+        if unknown.is_empty() {
+            return false;
+        }
+
+        self.resolve().await;
+        let owned = is_owned_by(pod.owner_references(), &self.owners);
+        if !owned {
+            self.rejected.extend(unknown);
+        }
+
+        owned
+    }
+}
+
+/// Get the uid of the replicasets owning the pod which are neither owners of the pods nor rejected
+///
+/// # Arguments
+/// * `references` - &[OwnerReference]
+/// * `owners` - &[String]
+/// * `rejected` - &BTreeSet<String>
+fn get_unknown_replica_sets(references: &[OwnerReference], owners: &[String], rejected: &BTreeSet<String>) -> Vec<String> {
+    references
+        .iter()
+        .filter(|reference| reference.kind == REPLICA_SET)
+        .filter(|reference| !owners.contains(&reference.uid) && !rejected.contains(&reference.uid))
+        .map(|reference| reference.uid.clone())
+        .collect()
+}
+
+/// Watch the pods matching the selector and move the forward to a ready pod when the current
+/// pod is deleted or is not ready anymore. Only the pods accepted by the filter are used
+///
+/// # Arguments
+/// * `api` - Api<Pod>
+/// * `selector` - String
+/// * `filter` - Option<OwnerFilter>
+/// * `tx` - Sender<String>
+pub async fn follow_pods(api: Api<Pod>, selector: String, mut filter: Option<OwnerFilter>, tx: Sender<String>) {
+    let params = ListParams::default().labels(&selector);
+    let mut stream = watcher(api, params).boxed();
+    let mut pods: BTreeMap<String, bool> = BTreeMap::new();
+
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(Event::Applied(pod)) => {
+                // a pod of another workload sharing the labels is ignored
+                match is_accepted(&mut filter, &pod).await {
+                    true => pods.insert(pod.name(), is_pod_ready(&pod)),
+                    false => pods.remove(&pod.name())
+                };
+            },
+            Ok(Event::Deleted(pod)) => {
+                pods.remove(&pod.name());
+            },
+            Ok(Event::Restarted(list)) => {
+                // every replicaset is checked again after a restart of the watch
+                if let Some(filter) = filter.as_mut() {
+                    filter.rejected.clear();
+                    filter.resolve().await;
+                }
+
+                pods = BTreeMap::new();
+                for pod in list {
+                    if is_accepted(&mut filter, &pod).await {
+                        pods.insert(pod.name(), is_pod_ready(&pod));
+                    }
+                }
+            },
+            Err(err) => {
+                log::warn!("Error while watching the pods matching `{selector}`: {err}");
+                tokio::time::sleep(WATCHER_RETRY_DELAY).await;
+                continue;
+            }
+        }
+
+        let current = tx.borrow().clone();
+        match select_pod(&current, &pods) {
+            Some(next) if next != current => {
+                log::info!("Moving the forward from the pod {current} to the pod {next}");
+                events::emit(events::Event::PodChanged { from: current.clone(), to: next.clone() });
+                if tx.send(next).is_err() {
+                    return;
+                }
+            },
+            Some(_) => {},
+            None => log::warn!("No ready pod is matching `{selector}`, the forward will move once a pod is ready")
+        }
+    }
+}
+
+/// Watch the pods until one of the pods accepted by the filter is ready. An error is returned when
+/// no pod is ready before the timeout
+///
+/// # Arguments
+/// * `api` - Api<Pod>
+/// * `params` - ListParams
+/// * `filter` - F
+/// * `timeout` - Duration
+/// * `target` - &str
+pub async fn wait_ready_pod<F>(
+    api: Api<Pod>,
+    params: ListParams,
+    filter: F,
+    timeout: Duration,
+    target: &str
+) -> Result<String, KubeErr> where
+    F: Fn(&Pod) -> bool
+{
+    log::info!("Waiting up to {}s for a ready pod of {target}", timeout.as_secs());
+    let wait = async {
+        let mut stream = watcher(api, params).boxed();
+        while let Some(event) = stream.next().await {
+            let pods = match event {
+                Ok(Event::Applied(pod)) => vec![pod],
+                Ok(Event::Restarted(list)) => list,
+                Ok(Event::Deleted(_)) => continue,
+                Err(err) => {
+                    log::warn!("Error while watching the pods of {target}: {err}");
+                    tokio::time::sleep(WATCHER_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+
+            if let Some(pod) = pods.iter().find(|pod| filter(pod) && is_pod_ready(pod)) {
+                return Some(pod.name());
+            }
+        }
+
+        None
+    };
+
+    match tokio::time::timeout(timeout, wait).await {
+        Ok(Some(pod)) => Ok(pod),
+        _ => Err(KubeErr::WaitTimeout(target.to_owned(), timeout.as_secs()))
+    }
+}
+
+/// Check whether the pod is accepted by the filter. Every pod is accepted without filter
+///
+/// # Arguments
+/// * `filter` - &mut Option<OwnerFilter>
+/// * `pod` - &Pod
+async fn is_accepted(filter: &mut Option<OwnerFilter>, pod: &Pod) -> bool {
+    match filter {
+        Some(filter) => filter.accept(pod).await,
+        None => true
+    }
+}
+
+/// Select the pod to forward. The current pod is kept as long as it's ready otherwise
+/// the first ready pod is selected
+///
+/// # Arguments
+/// * `current` - &str
+/// * `pods` - &BTreeMap<String, bool>
+fn select_pod(current: &str, pods: &BTreeMap<String, bool>) -> Option<String> {
+    if let Some(true) = pods.get(current) {
+        return Some(current.to_owned());
+    }
+
+    pods.iter()
+        .find(|(_, ready)| **ready)
+        .map(|(name, _)| name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expect_to_keep_current_ready_pod() {
+        let pods = BTreeMap::from([
+            ("api-1".to_owned(), true),
+            ("api-2".to_owned(), true)
+        ]);
+
+        assert_eq!(select_pod("api-2", &pods).unwrap(), "api-2");
+    }
+
+    #[test]
+    fn expect_to_move_to_ready_pod() {
+        let pods = BTreeMap::from([
+            ("api-1".to_owned(), false),
+            ("api-2".to_owned(), true)
+        ]);
+
+        assert_eq!(select_pod("api-1", &pods).unwrap(), "api-2");
+        assert_eq!(select_pod("api-0", &pods).unwrap(), "api-2");
+    }
+
+    #[test]
+    fn expect_to_not_select_pod_when_none_ready() {
+        let pods = BTreeMap::from([("api-1".to_owned(), false)]);
+
+        assert!(select_pod("api-1", &pods).is_none());
+    }
+
+    #[test]
+    fn expect_to_get_unknown_replica_sets() {
+        let references = vec![OwnerReference {
+            kind: REPLICA_SET.to_owned(),
+            name: "api-5d4f".to_owned(),
+            uid: "1234".to_owned(),
+            ..Default::default()
+        }];
+
+        assert_eq!(get_unknown_replica_sets(&references, &[], &BTreeSet::new()), vec!["1234".to_owned()]);
+        assert!(get_unknown_replica_sets(&references, &["1234".to_owned()], &BTreeSet::new()).is_empty());
+        assert!(get_unknown_replica_sets(&references, &[], &BTreeSet::from(["1234".to_owned()])).is_empty());
+    }
+}
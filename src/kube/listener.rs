@@ -1,71 +1,606 @@
 use std::{
+    future::Future,
     sync::Arc,
-    convert::Infallible,
-    net::SocketAddr
+    net::{SocketAddr, IpAddr, Ipv4Addr, Ipv6Addr},
+    time::Duration,
+    io::ErrorKind,
+    fmt
+};
+use tokio::{
+    sync::{Mutex, watch::Receiver},
+    net::TcpListener,
+    io::{AsyncRead, AsyncWrite}
 };
-use tokio::sync::Mutex;
 use hyper::{
     service::{make_service_fn, service_fn},
     client::conn::SendRequest,
+    rt::Executor,
+    server::conn::{AddrIncoming, AddrStream},
+    header,
     Body,
+    Request,
     Server
 };
-use futures_util::FutureExt;
-use crate::{utils, error::KubeErr};
+use kube::{Api, api::Portforwarder};
+use tokio_util::sync::CancellationToken;
+use k8s_openapi::api::core::v1::Pod;
+use clap::ArgEnum;
+use serde::{Serialize, Deserialize};
+use crate::{utils, daemon::logger, error::KubeErr, events::{self, Event, ErrorInfo}};
+use super::{
+    backoff::{Backoff, MAX_ATTEMPTS},
+    remote::RemoteError,
+    stats::{CountingStream, Stats, Status}
+};
 
 // Constant
 const STOP_SERVER_MSG: &str = "Stopping port forwarding";
-const CTRL_C_MSG: &str = "Use Ctrl-C / Cmd-C to stop the server";
-const LOCAL_ADDR: [u8; 4] = [127, 0, 0, 1];
+const LOCALHOST: &str = "localhost";
+const PROBE_DELAY: Duration = Duration::from_millis(500);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Mode used to carry the traffic between the local machine and the pod
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Http,
+    Tcp
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mode::Http => write!(f, "http"),
+            Mode::Tcp => write!(f, "tcp")
+        }
+    }
+}
+
+// Guard emitting the closing of an HTTP connection once hyper drops the service of the connection
+struct ConnectionGuard {
+    pod: String,
+    port: u16,
+    peer: SocketAddr
+}
 
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        events::emit(Event::ConnectionClosed {
+            pod: self.pod.clone(),
+            port: self.port,
+            peer: self.peer,
+            bytes: None,
+            error: None
+        });
+    }
+}
+
+// Executor of hyper spawning the tasks of the connections along with the id of the forward
+#[derive(Clone, Copy)]
+struct ForwardExecutor;
+
+impl<F> Executor<F> for ForwardExecutor where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static
+{
+    fn execute(&self, fut: F) {
+        logger::spawn(fut);
+    }
+}
+
+// Context used to open a dedicated port-forward stream for each local connection
+// The pod name is received through a channel as the targeted pod may be replaced.
+// The listeners are stopped once the shutdown token is cancelled
+#[derive(Clone)]
 pub struct Context {
-    ctx: Arc<Mutex<SendRequest<Body>>>
+    pod_api: Api<Pod>,
+    pod_name: Receiver<String>,
+    pod_port: u16,
+    stats: Arc<Stats>,
+    shutdown: CancellationToken
 }
 
 impl Context {
     /// Create a new Context
     ///
     /// # Arguments
-    /// * `sender` - SendRequest<Body>
-    pub fn new(sender: SendRequest<Body>) -> Context {
-        Context { ctx: Arc::new(Mutex::new(sender)) }
+    /// * `pod_api` - Api<Pod>
+    /// * `pod_name` - Receiver<String>
+    /// * `pod_port` - u16
+    /// * `stats` - Arc<Stats>
+    /// * `shutdown` - CancellationToken
+    pub fn new(
+        pod_api: Api<Pod>,
+        pod_name: Receiver<String>,
+        pod_port: u16,
+        stats: Arc<Stats>,
+        shutdown: CancellationToken
+    ) -> Context {
+        Context { pod_api, pod_name, pod_port, stats, shutdown }
     }
 
-    /// Forward the pod port to the local machine port by creating a new hyper server
+    /// Get the statistics of the forward
     ///
     /// # Arguments
     /// * `&self` - Self
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Get the port of the pod
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn pod_port(&self) -> u16 {
+        self.pod_port
+    }
+
+    /// Get the name of the pod which is currently targeted
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn pod_name(&self) -> String {
+        self.pod_name.borrow().clone()
+    }
+
+    /// Forward the pod port to the local machine port with the given mode. A server is listening
+    /// on each of the given addresses. The machine port 0 lets the system pick a free port.
+    /// The pod port is probed before being exposed
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `addresses` - &[IpAddr]
     /// * `machine_port` - u16
-    pub async fn port_forward_local(&self, machine_port: u16) -> Result<(), KubeErr> {
-        let make_service = make_service_fn(move |_conn| {
-            let context = self.ctx.clone();
-            let service = service_fn(move |req| utils::handle(context.clone(), req));
-            async move { Ok::<_, Infallible>(service) }
+    /// * `mode` - Mode
+    /// * `health` - Option<&str>
+    pub async fn port_forward_local(
+        &self,
+        addresses: &[IpAddr],
+        machine_port: u16,
+        mode: Mode,
+        health: Option<&str>
+    ) -> Result<(), KubeErr> {
+        let listeners = bind_listeners(addresses, machine_port).await?;
+        let local_port = listeners[0].local_addr()?.port();
+        if machine_port == 0 {
+            log::info!("The local port {local_port} has been picked for the pod port {}", self.pod_port);
+        }
+
+        // a failing probe does not prevent the forward as the container may listen later on
+        let res = self.probe(mode, health).await;
+        match &res {
+            Ok(_) => log::info!("The pod {}:{} is ready", self.pod_name(), self.pod_port),
+            Err(err) => {
+                log::warn!("The pod {}:{} is failing: {err}", self.pod_name(), self.pod_port);
+                self.stats.push_error(err.to_string());
+            }
+        }
+
+        events::emit(Event::Probe {
+            pod: self.pod_name(),
+            port: self.pod_port,
+            ready: res.is_ok(),
+            error: res.as_ref().err().map(ErrorInfo::from)
         });
 
-        // a oneshot channel is used only to listen for the ctrl-c command
-        // when a ctrl-c command has been executed by the user
-        // we're sending an unique message which will terminate gracefully terminate the server
-        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
-        let addr = SocketAddr::from((LOCAL_ADDR, machine_port));
-        let server = Server::bind(&addr)
-            .serve(make_service)
-            .with_graceful_shutdown(async {
-                rx.await.ok();
+        self.stats.set_local_port(local_port);
+        self.stats.set_status(Status::Up);
+
+        let servers = listeners
+            .into_iter()
+            .map(|listener| async move {
+                match mode {
+                    Mode::Http => self.serve_http(listener).await,
+                    Mode::Tcp => self.serve_tcp(listener).await
+                }
+            });
+
+        futures::future::try_join_all(servers).await?;
+
+        Ok(())
+    }
+
+    /// Open a new port-forward stream with the targeted pod. When the port-forward can't be opened
+    /// (e.g: the websocket with the API server dropped), the stream is reopened with a backoff
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    async fn open_stream(&self) -> Result<(impl AsyncRead + AsyncWrite + Unpin, Portforwarder, RemoteError), KubeErr> {
+        let mut backoff = Backoff::default();
+        loop {
+            let err = match self.try_open_stream().await {
+                Ok(res) => {
+                    if backoff.attempt() > 0 {
+                        log::info!("Reconnected to the pod {}:{}", self.pod_name(), self.pod_port);
+                        self.stats.set_status(Status::Up);
+                        events::emit(Event::Reconnected { pod: self.pod_name(), port: self.pod_port });
+                    }
+
+                    return Ok(res);
+                },
+                Err(err) => err
+            };
+
+            self.stats.push_error(err.to_string());
+            let delay = match backoff.next_delay() {
+                Some(delay) => delay,
+                None => return Err(err)
+            };
+
+            self.stats.set_status(Status::Reconnecting);
+            events::emit(Event::Reconnecting {
+                pod: self.pod_name(),
+                port: self.pod_port,
+                attempt: backoff.attempt(),
+                delay_ms: delay.as_millis(),
+                error: ErrorInfo::from(&err)
             });
 
-        log::info!("Exposing the pod to the local port of:  {addr}");
-        log::info!("{CTRL_C_MSG}");
+            log::warn!(
+                "Unable to open a stream with the pod {}:{}: {err}. Reconnecting in {delay:?} (attempt {}/{MAX_ATTEMPTS})",
+                self.pod_name(),
+                self.pod_port,
+                backoff.attempt()
+            );
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Try to open a new port-forward stream with the targeted pod. The error channel of the stream
+    /// is listened to get the errors reported by the pod
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    async fn try_open_stream(&self) -> Result<(impl AsyncRead + AsyncWrite + Unpin, Portforwarder, RemoteError), KubeErr> {
+        let mut forwarder = self.pod_api.portforward(&self.pod_name(), &[self.pod_port]).await?;
+        let stream = forwarder
+            .take_stream(self.pod_port)
+            .ok_or(KubeErr::ForwardPort)?;
+
+        let channel = forwarder
+            .take_error(self.pod_port)
+            .ok_or(KubeErr::ForwardPort)?;
+
+        let remote = RemoteError::listen(channel, self.pod_name(), self.pod_port, self.stats.clone());
+
+        Ok((stream, forwarder, remote))
+    }
+
+    /// Check that the container accepts connections on the pod port by opening a test stream.
+    /// In the HTTP mode, the health path is requested when it's provided
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `mode` - Mode
+    /// * `health` - Option<&str>
+    async fn probe(&self, mode: Mode, health: Option<&str>) -> Result<(), KubeErr> {
+        let (stream, forwarder, mut remote) = self.try_open_stream().await?;
+        let res = match (mode, health) {
+            (Mode::Http, Some(path)) => {
+                let res = self.probe_http(stream, path).await;
+                match remote.get().await {
+                    Some(err) => Err(err),
+                    None => res
+                }
+            },
+            // the pod reports on the error channel when the container refuses the connection
+            _ => match remote.wait(PROBE_DELAY).await {
+                Some(err) => Err(err),
+                None => Ok(())
+            }
+        };
+
+        forwarder.abort();
+        res
+    }
+
+    /// Request the health path over the stream. A response which is not a success or a redirection
+    /// is considered as failing
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `stream` - S
+    /// * `path` - &str
+    async fn probe_http<S>(&self, stream: S, path: &str) -> Result<(), KubeErr> where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static
+    {
+        let (mut sender, connection) = hyper::client::conn::handshake(stream).await?;
+        utils::listen_conn_error(connection);
+
+        let req = Request::get(path)
+            .header(header::HOST, format!("{}:{}", self.pod_name(), self.pod_port))
+            .body(Body::empty())
+            .map_err(|err| KubeErr::Probe(err.to_string()))?;
+
+        let response = tokio::time::timeout(PROBE_TIMEOUT, sender.send_request(req))
+            .await
+            .map_err(|_| KubeErr::ResponseTimeout(PROBE_TIMEOUT.as_secs()))?
+            .map_err(|err| KubeErr::Upstream(err.to_string()))?;
+
+        let status = response.status();
+        match status.is_success() || status.is_redirection() {
+            true => Ok(()),
+            false => Err(KubeErr::Probe(format!("{path} responded with {status}")))
+        }
+    }
+
+    /// Open a new port-forward stream and use it as an HTTP connection. The error reported by the pod
+    /// for the stream is returned along with the connection
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub async fn open_sender(&self) -> Result<(SendRequest<Body>, RemoteError), KubeErr> {
+        let (stream, forwarder, remote) = self.open_stream().await?;
+        let stream = CountingStream::new(stream, self.stats.clone());
+        let (sender, connection) = hyper::client::conn::handshake(stream).await?;
+
+        // listen to errors by spawning a new task
+        utils::listen_conn_error(connection);
+        utils::listen_forwarder_error(forwarder, self.pod_name(), self.pod_port);
+
+        Ok((sender, remote))
+    }
 
-        // Use to listen to the ctrl_c / cmd_c command
-        tokio::spawn(async move {
-            tokio::signal::ctrl_c().map(|_| ()).await;
-            log::warn!("{STOP_SERVER_MSG}");
-            let _ = tx.send(());
+    /// Forward the pod port to the local machine port by creating a new hyper server
+    /// Each accepted connection get its own port-forward stream
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `listener` - TcpListener
+    async fn serve_http(&self, listener: TcpListener) -> Result<(), KubeErr> {
+        let make_service = make_service_fn(move |conn: &AddrStream| {
+            let context = self.clone();
+            let guard = ConnectionGuard {
+                pod: context.pod_name(),
+                port: context.pod_port,
+                peer: conn.remote_addr()
+            };
+
+            context.stats.add_connection();
+            events::emit(Event::ConnectionOpened { pod: guard.pod.clone(), port: guard.port, peer: guard.peer });
+            // the stream with the pod is opened by the first request of the connection
+            let sender = Arc::new(Mutex::new(None));
+            // the guard lives as long as the service of the connection
+            let service = service_fn(move |req| {
+                let _ = &guard;
+                utils::handle(context.clone(), sender.clone(), req)
+            });
+
+            async move { Ok::<_, KubeErr>(service) }
         });
 
+        // the server is gracefully terminated once the shutdown token is cancelled
+        let addr = listener.local_addr()?;
+        let server = Server::builder(AddrIncoming::from_listener(listener)?)
+            .executor(ForwardExecutor)
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                self.shutdown.cancelled().await;
+                log::warn!("{STOP_SERVER_MSG}");
+                events::emit(Event::Shutdown { pod: self.pod_name(), port: self.pod_port });
+            });
+
+        log::info!("Exposing the pod {}:{} to the local port of:  {addr}", self.pod_name(), self.pod_port);
+        events::emit(Event::ForwardStarted { pod: self.pod_name(), port: self.pod_port, address: addr });
+
         server
             .await
             .map_err(|err| KubeErr::Network(err.to_string()))
     }
+
+    /// Forward the pod port to the local machine port by copying the raw bytes of each
+    /// accepted connection. Every connection is using its own port-forward stream
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `listener` - TcpListener
+    async fn serve_tcp(&self, listener: TcpListener) -> Result<(), KubeErr> {
+        let addr = listener.local_addr()?;
+        log::info!("Exposing the pod {}:{} to the local port of:  {addr}", self.pod_name(), self.pod_port);
+        events::emit(Event::ForwardStarted { pod: self.pod_name(), port: self.pod_port, address: addr });
+
+        loop {
+            tokio::select! {
+                res = listener.accept() => {
+                    let (socket, peer) = res?;
+                    let context = self.clone();
+                    context.stats.add_connection();
+                    events::emit(Event::ConnectionOpened { pod: context.pod_name(), port: context.pod_port, peer });
+
+                    logger::spawn(async move {
+                        let res = match context.open_stream().await {
+                            Ok((stream, forwarder, remote)) => utils::forward_connection(socket, stream, forwarder, remote, context.stats.clone()).await,
+                            Err(err) => Err(err)
+                        };
+
+                        if let Err(err) = &res {
+                            log::error!("error in connection {peer}: {err}");
+                            context.stats.push_error(err.to_string());
+                        }
+
+                        events::emit(Event::ConnectionClosed {
+                            pod: context.pod_name(),
+                            port: context.pod_port,
+                            peer,
+                            bytes: res.as_ref().ok().copied(),
+                            error: res.as_ref().err().map(ErrorInfo::from)
+                        });
+                    });
+                },
+                _ = self.shutdown.cancelled() => {
+                    log::warn!("{STOP_SERVER_MSG}");
+                    events::emit(Event::Shutdown { pod: self.pod_name(), port: self.pod_port });
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Resolve the addresses provided by the user. The localhost address is resolved to both
+/// the IPv4 and the IPv6 loopback addresses
+///
+/// # Arguments
+/// * `addresses` - &[String]
+pub fn resolve_addresses(addresses: &[String]) -> Result<Vec<IpAddr>, KubeErr> {
+    let mut ips = Vec::new();
+    for address in addresses {
+        let resolved = match address.as_str() {
+            LOCALHOST => vec![IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)],
+            ip => vec![ip.parse::<IpAddr>().map_err(|_| KubeErr::WrongAddress(ip.to_owned()))?]
+        };
+
+        for ip in resolved {
+            if !ips.contains(&ip) {
+                ips.push(ip);
+            }
+        }
+    }
+
+    Ok(ips)
+}
+
+/// Bind a listener on each address. Like kubectl, every address must be bound. Only a loopback of
+/// localhost whose address family is not supported (e.g: IPv6 is disabled) is skipped as long as
+/// the other loopback is bound. When the port is 0, the port picked for the first address is used
+/// for the other addresses
+///
+/// # Arguments
+/// * `addresses` - &[IpAddr]
+/// * `port` - u16
+async fn bind_listeners(addresses: &[IpAddr], port: u16) -> Result<Vec<TcpListener>, KubeErr> {
+    let mut listeners = Vec::new();
+    let mut skipped = None;
+    let mut port = port;
+    for ip in addresses {
+        let addr = SocketAddr::new(*ip, port);
+        match TcpListener::bind(addr).await {
+            Ok(listener) => {
+                // the port picked by the system is reused for the other addresses
+                port = listener.local_addr()?.port();
+                listeners.push(listener);
+            },
+            Err(err) if is_skippable_bind_error(addresses, ip, &err) => {
+                log::warn!("Unable to listen on {addr}: {err}");
+                skipped = Some(KubeErr::Bind(addr.to_string(), err.to_string()));
+            },
+            Err(err) => return Err(KubeErr::Bind(addr.to_string(), err.to_string()))
+        }
+    }
+
+    match (listeners.is_empty(), skipped) {
+        (true, Some(err)) => Err(err),
+        _ => Ok(listeners)
+    }
+}
+
+/// Check whether the address can be skipped when it can't be bound. Only a loopback of localhost
+/// whose address family is not available can be skipped
+///
+/// # Arguments
+/// * `addresses` - &[IpAddr]
+/// * `ip` - &IpAddr
+/// * `err` - &std::io::Error
+pub fn is_skippable_bind_error(addresses: &[IpAddr], ip: &IpAddr, err: &std::io::Error) -> bool {
+    is_localhost(addresses, ip) && is_family_error(err)
+}
+
+/// Check whether the address is one of the loopbacks resolved from localhost
+///
+/// # Arguments
+/// * `addresses` - &[IpAddr]
+/// * `ip` - &IpAddr
+fn is_localhost(addresses: &[IpAddr], ip: &IpAddr) -> bool {
+    ip.is_loopback()
+        && addresses.contains(&IpAddr::V4(Ipv4Addr::LOCALHOST))
+        && addresses.contains(&IpAddr::V6(Ipv6Addr::LOCALHOST))
+}
+
+/// Check whether the bind failed because the address family is not available on the machine
+///
+/// # Arguments
+/// * `err` - &std::io::Error
+fn is_family_error(err: &std::io::Error) -> bool {
+    err.kind() == ErrorKind::AddrNotAvailable || err.raw_os_error() == Some(libc::EAFNOSUPPORT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expect_to_resolve_localhost_to_both_loopbacks() {
+        let ips = resolve_addresses(&[LOCALHOST.to_owned()]).unwrap();
+
+        assert_eq!(ips, vec![
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(Ipv6Addr::LOCALHOST)
+        ]);
+    }
+
+    #[test]
+    fn expect_to_resolve_addresses_without_duplicates() {
+        let addresses = vec!["0.0.0.0".to_owned(), "::1".to_owned(), LOCALHOST.to_owned()];
+        let ips = resolve_addresses(&addresses).unwrap();
+
+        assert_eq!(ips, vec![
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(Ipv6Addr::LOCALHOST),
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        ]);
+    }
+
+    #[test]
+    fn expect_to_not_resolve_wrong_address() {
+        assert!(resolve_addresses(&["foo".to_owned()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn expect_to_bind_available_addresses() {
+        let listeners = bind_listeners(&[IpAddr::V4(Ipv4Addr::LOCALHOST)], 0).await.unwrap();
+
+        assert_eq!(listeners.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn expect_to_not_bind_when_an_address_fails() {
+        // the port is used on the second address only. The test is skipped without IPv6
+        let used = match std::net::TcpListener::bind((Ipv6Addr::LOCALHOST, 0)) {
+            Ok(used) => used,
+            Err(_) => return
+        };
+
+        let port = used.local_addr().unwrap().port();
+        let addresses = [IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)];
+
+        assert!(bind_listeners(&addresses, port).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn expect_to_not_skip_a_used_localhost_port() {
+        let used = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = used.local_addr().unwrap().port();
+        let addresses = resolve_addresses(&[LOCALHOST.to_owned()]).unwrap();
+
+        assert!(bind_listeners(&addresses, port).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn expect_to_bind_the_same_picked_port() {
+        // the test is skipped without IPv6
+        if std::net::TcpListener::bind((Ipv6Addr::LOCALHOST, 0)).is_err() {
+            return;
+        }
+
+        let addresses = [IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)];
+        let listeners = bind_listeners(&addresses, 0).await.unwrap();
+        let ports: Vec<_> = listeners
+            .iter()
+            .map(|l| l.local_addr().unwrap().port())
+            .collect();
+
+        assert_eq!(ports.len(), 2);
+        assert_eq!(ports[0], ports[1]);
+    }
 }
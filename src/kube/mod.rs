@@ -5,10 +5,37 @@ use kube::{
 };
 use crate::error::KubeErr;
 
+// Constant
+// Label used when the current context of the kubeconfig is used
+pub const CURRENT_CONTEXT: &str = "current";
+
 pub mod pod;
 pub mod ns;
+pub mod listener;
+pub mod port;
+pub mod workload;
+pub mod follow;
+pub mod service;
+pub mod resource;
+pub mod stats;
+pub mod remote;
 mod container;
-mod listener;
+mod backoff;
+
+/// Get the default namespace of the provided context
+///
+/// # Arguments
+/// * `context` - &Option<String>
+pub async fn get_default_namespace(context: &Option<String>) -> Result<String, KubeErr> {
+    let mut options = KubeConfigOptions::default();
+    if let Some(ctx) = context {
+        options.context = Some(ctx.to_owned());
+    }
+
+    let config = Config::from_kubeconfig(&options).await?;
+
+    Ok(config.default_namespace)
+}
 
 /// Authenticate with the Kubernetes cluster based on the provided context
 ///
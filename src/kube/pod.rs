@@ -1,45 +1,147 @@
+use std::fmt;
 use kube::{
     api::{Api, ListParams},
     ResourceExt
 };
 use k8s_openapi::api::core::v1::Pod;
+use serde::{Serialize, Deserialize};
 use super::{
     container::ContainerWrapper,
-    listener::Context,
+    listener::Mode,
+    port::{DeclaredPort, SuggestedPort},
+    workload::is_owned_by,
     *
 };
 use crate::error::KubeErr;
-use crate::utils;
+
+// Constant
+const RUNNING_PHASE: &str = "Running";
+const TERMINATING_PHASE: &str = "Terminating";
+const UNKNOWN_PHASE: &str = "Unknown";
+
+// Resource which owns a pod (e.g: the ReplicaSet of a Deployment)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PodOwner {
+    pub kind: String,
+    pub name: String,
+    pub uid: String
+}
+
+// Phase and readiness of a pod. The phase is replaced by the reason of a container which is not ready
+// (e.g: CrashLoopBackOff) or by Terminating when the pod is being deleted. The reason of a terminated container
+// is only used when the pod is not running as a completed sidecar doesn't prevent the pod from running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodState {
+    pub phase: String,
+    pub ready: bool,
+    pub ready_containers: usize,
+    pub containers: usize
+}
+
+impl fmt::Display for PodState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}/{}", self.phase, self.ready_containers, self.containers)
+    }
+}
+
+impl From<&Pod> for PodState {
+    fn from(pod: &Pod) -> Self {
+        let status = pod.status.clone().unwrap_or_default();
+        let statuses = status.container_statuses.unwrap_or_default();
+        let running = status.phase.as_deref() == Some(RUNNING_PHASE);
+        let waiting = statuses
+            .iter()
+            .filter(|s| !s.ready)
+            .filter_map(|s| s.state.as_ref())
+            .find_map(|state| state.waiting
+                .as_ref()
+                .and_then(|w| w.reason.clone())
+                .or_else(|| match running {
+                    true => None,
+                    false => state.terminated.as_ref().and_then(|t| t.reason.clone())
+                })
+            );
+
+        let phase = match (pod.metadata.deletion_timestamp.is_some(), waiting) {
+            (true, _) => TERMINATING_PHASE.to_owned(),
+            (false, Some(reason)) => reason,
+            (false, None) => status.phase.unwrap_or_else(|| UNKNOWN_PHASE.to_owned())
+        };
+
+        PodState {
+            phase,
+            ready: is_pod_ready(pod),
+            ready_containers: statuses.iter().filter(|s| s.ready).count(),
+            containers: statuses.len()
+        }
+    }
+}
+
+impl PodState {
+    /// Check whether the containers of the pod are running. A running pod can be forwarded even
+    /// when it's not ready
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn is_running(&self) -> bool {
+        self.phase == RUNNING_PHASE
+    }
+}
+
+// Pod proposed by the prompt along with its state. The pods which are not ready are flagged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodOption {
+    pub name: String,
+    pub state: PodState
+}
+
+impl fmt::Display for PodOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.name, self.state)?;
+        if !self.state.ready {
+            write!(f, " (not ready)")?;
+        }
+
+        Ok(())
+    }
+}
 
 // Struct used to improve work on pods
 pub struct PodsList {
-    client: Option<Client>,
-    namespace: String,
     pods: Vec<Pod>,
     selected_pod: Option<Pod>,
     container_wrapper: ContainerWrapper
 }
 
+impl From<Vec<Pod>> for PodsList {
+    fn from(pods: Vec<Pod>) -> Self {
+        PodsList {
+            pods,
+            selected_pod: None,
+            container_wrapper: ContainerWrapper::default()
+        }
+    }
+}
+
 impl PodsList {
     /// Create a new PodsList structure
     ///
     /// # Arguments
     /// * `context` - Option<String>
     /// * `ns` - &str
-    pub async fn new(context: Option<String>, ns: &str) -> Result<PodsList, KubeErr> {
+    /// * `selector` - &Option<String>
+    pub async fn new(context: Option<String>, ns: &str, selector: &Option<String>) -> Result<PodsList, KubeErr> {
         let client = authenticate_with_cluster(&context).await?;
-        let pod_api: Api<Pod> = Api::namespaced(client.clone(), ns);
-        let list = pod_api.list(&ListParams::default()).await?;
+        let pod_api: Api<Pod> = Api::namespaced(client, ns);
 
-        let pods = PodsList {
-            client: Some(client),
-            namespace: ns.to_owned(),
-            pods: list.items,
-            selected_pod: None,
-            container_wrapper: ContainerWrapper::default()
-        };
+        let mut params = ListParams::default();
+        if let Some(selector) = selector {
+            params = params.labels(selector);
+        }
+
+        let list = pod_api.list(&params).await?;
 
-        Ok(pods)
+        Ok(PodsList::from(list.items))
     }
 
     /// Get a list of pod name for the list of pods that has been founded
@@ -54,11 +156,56 @@ impl PodsList {
             .collect::<Vec<_>>()
     }
 
+    /// Get the pods along with their state to be proposed by the prompt
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn get_pod_options(&self) -> Vec<PodOption> {
+        self.pods
+            .iter()
+            .map(|p| PodOption { name: p.name(), state: PodState::from(p) })
+            .collect()
+    }
+
+    /// Get the state of a pod by its name
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `name` - &str
+    pub fn get_pod_state(&self, name: &str) -> Option<PodState> {
+        self.pods
+            .iter()
+            .find(|p| p.metadata.name.as_deref() == Some(name))
+            .map(PodState::from)
+    }
+
+    /// Keep only the pods which are owned by one of the given owners
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `owners` - &[String]
+    pub fn filter_owned_by(&mut self, owners: &[String]) -> &mut Self {
+        self.pods.retain(|p| is_owned_by(p.owner_references(), owners));
+        self
+    }
+
+    /// Get the name of the first pod which is ready
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn get_ready_pod_name(&self) -> Option<String> {
+        self.pods
+            .iter()
+            .find(|p| is_pod_ready(p))
+            .and_then(|p| p.metadata.name.clone())
+    }
+
     /// Save the selected pod on the current struct
     ///
     /// # Arguments
     /// * `&mut self` - Self
     /// * `pod_name` - String
+    #[allow(clippy::iter_overeager_cloned)]
     pub fn set_selected_pod(&mut self, pod_name: String) -> &mut Self {
         let mut pod: Vec<_> = self.pods
             .iter()
@@ -93,7 +240,7 @@ impl PodsList {
     /// # Arguments
     /// * `&self` - Self
     /// * `selected_container` - Option<String>
-    pub fn get_port_for_container(&self, selected_container: String) -> Option<Vec<i32>> {
+    pub fn get_port_for_container(&self, selected_container: String) -> Option<Vec<DeclaredPort>> {
         if let Some(pod) = self.selected_pod.to_owned() {
             if let Some(spec) = pod.spec {
                 let mut containers = ContainerWrapper::new(spec.containers);
@@ -108,41 +255,83 @@ impl PodsList {
         None
     }
 
-    /// Expose the pod based on the container pord and the given user port
-    /// Implementation is highly inspired by the link below
-    /// @link https://github.com/kube-rs/kube-rs/blob/master/examples/pod_portforward_bind.rs
+    /// Get the ports of the selected container which are not declared but found in its probes or its
+    /// environment variables
     ///
     /// # Arguments
     /// * `&self` - Self
-    /// * `selected_port` - u16
-    /// * `user_port` - u16
-    pub async fn expose_pod(&self, selected_port: u16, user_port: u16) -> Result<(), KubeErr> {
-        if self.selected_pod.is_none() {
-            return Err(KubeErr::SelectedPod);
-        }
-
-        if self.client.is_none() {
-            return Err(KubeErr::Kube("Could not connect retrieve client handler".to_owned()));
+    /// * `selected_container` - String
+    pub fn get_suggested_ports(&self, selected_container: String) -> Vec<SuggestedPort> {
+        match self.selected_pod.as_ref().and_then(|pod| pod.spec.clone()) {
+            Some(spec) => ContainerWrapper::new(spec.containers)
+                .set_selected_container(selected_container)
+                .get_suggested_ports(),
+            None => Vec::new()
         }
+    }
 
-        let selected_pod = self.selected_pod.to_owned().unwrap();
-        let client = self.client.clone().unwrap();
-        let pod_api: Api<Pod> = Api::namespaced(client, &self.namespace);
+    /// Get the port number of a named port declared by any container of the selected pod
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `name` - &str
+    pub fn get_port_by_name(&self, name: &str) -> Option<i32> {
+        self.selected_pod
+            .as_ref()
+            .and_then(|pod| pod.spec.as_ref())
+            .and_then(|spec| spec.containers
+                .iter()
+                .flat_map(|c| c.ports.iter().flatten())
+                .find(|p| p.name.as_deref() == Some(name))
+                .map(|p| p.container_port)
+            )
+    }
 
-        let mut forwarder = pod_api.portforward(&selected_pod.name(), &[selected_port as u16]).await?;
-        let local_port = forwarder
-            .take_stream(selected_port as u16)
-            .ok_or(KubeErr::ForwardPort)?;
+    /// Get the mode which should be used by default to forward the port of the selected container
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `selected_container` - String
+    /// * `port` - i32
+    pub fn get_mode_for_port(&self, selected_container: String, port: i32) -> Mode {
+        if let Some(pod) = self.selected_pod.to_owned() {
+            if let Some(spec) = pod.spec {
+                let mut containers = ContainerWrapper::new(spec.containers);
+                return containers
+                    .set_selected_container(selected_container)
+                    .get_mode_for_port(port);
+            }
+        }
 
-        let (sender, connection) = hyper::client::conn::handshake(local_port).await?;
+        Mode::Tcp
+    }
 
-        // listen to errors by spawning a new task
-        utils::listen_conn_error(connection);
-        utils::listen_forwarder_error(forwarder);
+    /// Get the name of the selected pod
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn get_selected_pod_name(&self) -> Option<String> {
+        self.selected_pod
+            .as_ref()
+            .map(|pod| pod.name())
+    }
 
-        Context::new(sender)
-            .port_forward_local(user_port)
-            .await
+    /// Get the owner of the selected pod. The controller of the pod is preferred over the other owners
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn get_selected_pod_owner(&self) -> Option<PodOwner> {
+        let references = self.selected_pod.as_ref()?.owner_references();
+        let reference = references
+            .iter()
+            .find(|r| r.controller == Some(true))
+            .or_else(|| references.first())?;
+
+        Some(PodOwner {
+            kind: reference.kind.clone(),
+            name: reference.name.clone(),
+            uid: reference.uid.clone()
+        })
     }
 
     /// Set the containers for a selected pod to expose
@@ -158,10 +347,40 @@ impl PodsList {
     }
 }
 
+/// Check whether the pod is ready to receive traffic and is not being deleted
+///
+/// # Arguments
+/// * `pod` - &Pod
+pub fn is_pod_ready(pod: &Pod) -> bool {
+    if pod.metadata.deletion_timestamp.is_some() {
+        return false;
+    }
+
+    pod.status
+        .as_ref()
+        .and_then(|status| status.conditions.as_ref())
+        .map(|conditions| conditions
+            .iter()
+            .any(|c| c.type_ == "Ready" && c.status == "True")
+        )
+        .unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
-    use k8s_openapi::api::core::v1::{PodSpec, Container, ContainerPort};
+    use k8s_openapi::api::core::v1::{
+        PodSpec,
+        PodStatus,
+        PodCondition,
+        Container,
+        ContainerPort,
+        ContainerStatus,
+        ContainerState,
+        ContainerStateWaiting,
+        ContainerStateTerminated
+    };
     use kube::core::ObjectMeta;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
 
     use super::*;
 
@@ -187,8 +406,6 @@ mod tests {
         };
 
         PodsList {
-            client: None,
-            namespace: "default".to_owned(),
             pods: vec![
                 Pod {
                     metadata: ObjectMeta {
@@ -204,6 +421,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn expect_to_get_pod_names() {
         let pod_list = setup();
         let names = pod_list.get_pod_name_list();
@@ -212,6 +430,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::get_first)]
     fn expect_to_get_pod_port_container() {
         let mut pod_list = setup();
         let container_port = pod_list
@@ -221,7 +440,100 @@ mod tests {
         assert!(container_port.is_some());
         let container_port = container_port.unwrap();
 
-        assert_eq!(*container_port.get(0).unwrap(), 3000);
+        assert_eq!(container_port.get(0).unwrap().port, 3000);
+    }
+
+    #[test]
+    fn expect_to_get_mode_for_pod_port() {
+        let mut pod_list = setup();
+        let mode = pod_list
+            .set_selected_pod("foo".to_owned())
+            .get_mode_for_port("foo".to_owned(), 3000);
+
+        assert_eq!(mode, Mode::Http);
+    }
+
+    #[test]
+    fn expect_to_get_selected_pod_name() {
+        let mut pod_list = setup();
+        let name = pod_list
+            .set_selected_pod("foo".to_owned())
+            .get_selected_pod_name();
+
+        assert_eq!(name.unwrap(), "foo");
+    }
+
+    #[test]
+    fn expect_to_filter_pods_owned_by() {
+        let mut pod_list = setup();
+        pod_list.pods[0].metadata.owner_references = Some(vec![OwnerReference {
+            uid: "1234".to_owned(),
+            ..Default::default()
+        }]);
+
+        let names = pod_list
+            .filter_owned_by(&["1234".to_owned()])
+            .get_pod_name_list();
+        assert_eq!(names, vec!["foo".to_owned()]);
+
+        let names = pod_list
+            .filter_owned_by(&["5678".to_owned()])
+            .get_pod_name_list();
+        assert!(names.is_empty());
+    }
+
+    #[test]
+    fn expect_to_get_selected_pod_owner() {
+        let mut pod_list = setup();
+        pod_list.pods[0].metadata.owner_references = Some(vec![
+            OwnerReference {
+                kind: "Node".to_owned(),
+                name: "node-1".to_owned(),
+                uid: "5678".to_owned(),
+                ..Default::default()
+            },
+            OwnerReference {
+                kind: "ReplicaSet".to_owned(),
+                name: "api-5d4f".to_owned(),
+                uid: "1234".to_owned(),
+                controller: Some(true),
+                ..Default::default()
+            }
+        ]);
+
+        let owner = pod_list
+            .set_selected_pod("foo".to_owned())
+            .get_selected_pod_owner()
+            .unwrap();
+
+        assert_eq!(owner.kind, "ReplicaSet");
+        assert_eq!(owner.uid, "1234");
+    }
+
+    #[test]
+    fn expect_to_get_ready_pod_name() {
+        let mut pod_list = setup();
+        assert!(pod_list.get_ready_pod_name().is_none());
+
+        pod_list.pods[0].status = Some(PodStatus {
+            conditions: Some(vec![PodCondition {
+                type_: "Ready".to_owned(),
+                status: "True".to_owned(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        });
+
+        assert_eq!(pod_list.get_ready_pod_name().unwrap(), "foo");
+    }
+
+    #[test]
+    fn expect_to_get_port_by_name() {
+        let mut pod_list = setup();
+        pod_list.set_selected_pod("foo".to_owned());
+
+        assert_eq!(pod_list.get_port_by_name("Http").unwrap(), 3000);
+        assert!(pod_list.get_port_by_name("metrics").is_none());
     }
 
     #[test]
@@ -232,4 +544,84 @@ mod tests {
 
         assert!(container_port.is_none());
     }
+
+    #[test]
+    fn expect_to_get_crash_loop_pod_state() {
+        let mut pod_list = setup();
+        pod_list.pods[0].status = Some(PodStatus {
+            phase: Some("Running".to_owned()),
+            container_statuses: Some(vec![ContainerStatus {
+                name: "foo".to_owned(),
+                ready: false,
+                state: Some(ContainerState {
+                    waiting: Some(ContainerStateWaiting {
+                        reason: Some("CrashLoopBackOff".to_owned()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        });
+
+        let state = pod_list.get_pod_state("foo").unwrap();
+        assert!(!state.is_running());
+        assert_eq!(pod_list.get_pod_options()[0].to_string(), "foo [CrashLoopBackOff 0/1] (not ready)");
+    }
+
+    #[test]
+    fn expect_to_get_ready_pod_state() {
+        let mut pod_list = setup();
+        pod_list.pods[0].status = Some(PodStatus {
+            phase: Some("Running".to_owned()),
+            conditions: Some(vec![PodCondition {
+                type_: "Ready".to_owned(),
+                status: "True".to_owned(),
+                ..Default::default()
+            }]),
+            container_statuses: Some(vec![ContainerStatus {
+                name: "foo".to_owned(),
+                ready: true,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        });
+
+        let state = pod_list.get_pod_state("foo").unwrap();
+        assert!(state.is_running());
+        assert_eq!(pod_list.get_pod_options()[0].to_string(), "foo [Running 1/1]");
+    }
+
+    #[test]
+    fn expect_to_get_running_pod_state_with_completed_sidecar() {
+        let mut pod_list = setup();
+        pod_list.pods[0].status = Some(PodStatus {
+            phase: Some("Running".to_owned()),
+            container_statuses: Some(vec![
+                ContainerStatus {
+                    name: "foo".to_owned(),
+                    ready: true,
+                    ..Default::default()
+                },
+                ContainerStatus {
+                    name: "sidecar".to_owned(),
+                    ready: false,
+                    state: Some(ContainerState {
+                        terminated: Some(ContainerStateTerminated {
+                            reason: Some("Completed".to_owned()),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+            ]),
+            ..Default::default()
+        });
+
+        let state = pod_list.get_pod_state("foo").unwrap();
+        assert!(state.is_running());
+        assert_eq!(state.to_string(), "Running 1/2");
+    }
 }
@@ -0,0 +1,345 @@
+use std::{fmt, str::FromStr, net::{IpAddr, Ipv4Addr, TcpListener}};
+use serde::{Serialize, Deserialize};
+use super::listener::{self, Mode};
+use crate::error::KubeErr;
+
+// Ports which are usually serving plain HTTP when they're not named
+const HTTP_PORTS: [i32; 3] = [80, 8000, 8080];
+// Local port used to let the system pick a free port
+pub const AUTO_PORT: &str = "auto";
+const TCP_PROTOCOL: &str = "TCP";
+// Maximum length of a port name (IANA_SVC_NAME)
+const PORT_NAME_MAX_LEN: usize = 15;
+
+// Port of the pod provided with the CLI. It's either a number or the name of a declared port (e.g: http)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemotePort {
+    Number(u16),
+    Name(String)
+}
+
+impl FromStr for RemotePort {
+    type Err = KubeErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // a port name contains at least a letter, a value made of digits only is a port number
+        if s.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(RemotePort::Number(s.parse::<u16>()?));
+        }
+
+        let valid = s.len() <= PORT_NAME_MAX_LEN
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+
+        match valid {
+            true => Ok(RemotePort::Name(s.to_owned())),
+            false => Err(KubeErr::WrongPort)
+        }
+    }
+}
+
+impl fmt::Display for RemotePort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemotePort::Number(port) => write!(f, "{port}"),
+            RemotePort::Name(name) => write!(f, "{name}")
+        }
+    }
+}
+
+// Port provided with the CLI with the format remote[:local]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortArg {
+    pub remote: RemotePort,
+    pub local: Option<u16>
+}
+
+impl FromStr for PortArg {
+    type Err = KubeErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (remote, local) = match s.split_once(':') {
+            Some((remote, local)) => (remote, Some(parse_local_port(local)?)),
+            None => (s, None)
+        };
+
+        Ok(PortArg {
+            remote: remote.parse()?,
+            local
+        })
+    }
+}
+
+impl PortArg {
+    /// Get the number of the remote port. A named port is resolved with the given lookup
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `lookup` - F
+    pub fn resolve<F>(&self, lookup: F) -> Result<u16, KubeErr> where
+        F: FnOnce(&str) -> Option<i32>
+    {
+        match &self.remote {
+            RemotePort::Number(port) => Ok(*port),
+            RemotePort::Name(name) => lookup(name)
+                .map(|port| port as u16)
+                .ok_or_else(|| KubeErr::UnknownPortName(name.to_owned()))
+        }
+    }
+}
+
+// Port declared by a container or a service along with its name and its protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclaredPort {
+    pub port: i32,
+    pub name: Option<String>,
+    pub protocol: String
+}
+
+impl fmt::Display for DeclaredPort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.port, self.protocol.to_uppercase())?;
+        if let Some(name) = &self.name {
+            write!(f, " ({name})")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DeclaredPort {
+    /// Create a new DeclaredPort. The protocol is TCP when it's not specified
+    ///
+    /// # Arguments
+    /// * `port` - i32
+    /// * `name` - Option<String>
+    /// * `protocol` - Option<String>
+    pub fn new(port: i32, name: Option<String>, protocol: Option<String>) -> DeclaredPort {
+        DeclaredPort {
+            port,
+            name,
+            protocol: protocol.unwrap_or_else(|| TCP_PROTOCOL.to_owned())
+        }
+    }
+
+    /// Check whether the port is using TCP. The port-forward only supports TCP
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn is_tcp(&self) -> bool {
+        self.protocol.eq_ignore_ascii_case(TCP_PROTOCOL)
+    }
+}
+
+// Where a port which is not declared by the container has been found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortSource {
+    Probe,
+    Env
+}
+
+// Port which is not declared by the container but which the container likely listens on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuggestedPort {
+    pub port: i32,
+    pub source: PortSource
+}
+
+impl fmt::Display for SuggestedPort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source {
+            PortSource::Probe => write!(f, "{} (found in the probes)", self.port),
+            PortSource::Env => write!(f, "{} (found in the env)", self.port)
+        }
+    }
+}
+
+// Mapping between a port of the pod and a port of the local machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortMapping {
+    pub remote: u16,
+    pub local: u16,
+    pub mode: Mode
+}
+
+/// Parse a local port. The port 0 or auto let the system pick a free port
+///
+/// # Arguments
+/// * `port` - &str
+pub fn parse_local_port(port: &str) -> Result<u16, KubeErr> {
+    match port.trim() {
+        AUTO_PORT => Ok(0),
+        port => Ok(port.parse::<u16>()?)
+    }
+}
+
+/// Parse a list of ports separated by a comma
+///
+/// # Arguments
+/// * `input` - &str
+pub fn parse_ports(input: &str) -> Result<Vec<u16>, KubeErr> {
+    let ports = input
+        .split(',')
+        .map(str::trim)
+        .filter(|port| !port.is_empty())
+        .map(|port| match port.parse::<u16>()? {
+            0 => Err(KubeErr::WrongPort),
+            port => Ok(port)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match ports.is_empty() {
+        true => Err(KubeErr::WrongPort),
+        false => Ok(ports)
+    }
+}
+
+/// Check whether the local port is free on every address the listeners are bound to. The port 0 is always
+/// available as the system picks a free port. The IPv4 loopback is checked when no address is provided
+///
+/// # Arguments
+/// * `addresses` - &[IpAddr]
+/// * `port` - u16
+pub fn is_port_available(addresses: &[IpAddr], port: u16) -> bool {
+    if port == 0 {
+        return true;
+    }
+
+    let loopback = [IpAddr::V4(Ipv4Addr::LOCALHOST)];
+    let addresses = match addresses.is_empty() {
+        true => &loopback[..],
+        false => addresses
+    };
+
+    // the listeners are kept until every address is checked as the addresses may overlap (e.g: 0.0.0.0)
+    let mut listeners = Vec::new();
+    for ip in addresses {
+        match TcpListener::bind((*ip, port)) {
+            Ok(listener) => listeners.push(listener),
+            Err(err) if listener::is_skippable_bind_error(addresses, ip, &err) => {},
+            Err(_) => return false
+        }
+    }
+
+    !listeners.is_empty()
+}
+
+/// Guess the mode to use for a port. The port is considered as an HTTP port if it's named http
+/// (e.g: http, http-metrics) or if it's a common HTTP port
+///
+/// # Arguments
+/// * `name` - Option<&str>
+/// * `port` - i32
+pub fn guess_mode(name: Option<&str>, port: i32) -> Mode {
+    let is_http = match name {
+        Some(name) => {
+            let name = name.to_lowercase();
+            name.starts_with("http") && !name.starts_with("https")
+        },
+        None => HTTP_PORTS.contains(&port)
+    };
+
+    match is_http {
+        true => Mode::Http,
+        false => Mode::Tcp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+    use super::*;
+
+    #[test]
+    fn expect_to_parse_ports() {
+        assert_eq!(parse_ports("6060, 8081,").unwrap(), vec![6060, 8081]);
+        assert!(parse_ports("").is_err());
+        assert!(parse_ports("0").is_err());
+        assert!(parse_ports("pprof").is_err());
+    }
+
+    #[test]
+    fn expect_to_parse_remote_port() {
+        let port = PortArg::from_str("8080").unwrap();
+
+        assert_eq!(port.remote, RemotePort::Number(8080));
+        assert!(port.local.is_none());
+    }
+
+    #[test]
+    fn expect_to_parse_remote_and_local_port() {
+        let port = PortArg::from_str("8080:3000").unwrap();
+
+        assert_eq!(port.remote, RemotePort::Number(8080));
+        assert_eq!(port.local, Some(3000));
+    }
+
+    #[test]
+    fn expect_to_parse_named_port() {
+        let port = PortArg::from_str("http:3000").unwrap();
+
+        assert_eq!(port.remote, RemotePort::Name("http".to_owned()));
+        assert_eq!(port.resolve(|name| (name == "http").then_some(8080)).unwrap(), 8080);
+        assert!(port.resolve(|_| None).is_err());
+    }
+
+    #[test]
+    fn expect_to_display_declared_port() {
+        let port = DeclaredPort::new(8080, Some("http".to_owned()), None);
+        let udp = DeclaredPort::new(53, None, Some("UDP".to_owned()));
+
+        assert_eq!(port.to_string(), "8080/TCP (http)");
+        assert!(port.is_tcp());
+        assert_eq!(udp.to_string(), "53/UDP");
+        assert!(!udp.is_tcp());
+    }
+
+    #[test]
+    fn expect_to_parse_auto_local_port() {
+        assert_eq!(PortArg::from_str("8080:auto").unwrap().local, Some(0));
+        assert_eq!(parse_local_port("0").unwrap(), 0);
+        assert!(parse_local_port("foo").is_err());
+    }
+
+    #[test]
+    fn expect_to_check_port_availability() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let addresses = [IpAddr::V6(Ipv6Addr::LOCALHOST), IpAddr::V4(Ipv4Addr::LOCALHOST)];
+
+        assert!(!is_port_available(&[], port));
+        assert!(!is_port_available(&addresses, port));
+        assert!(is_port_available(&[], 0));
+    }
+
+    #[test]
+    fn expect_to_check_port_availability_on_every_address() {
+        // the port is used on the IPv6 loopback only. The test is skipped without IPv6
+        let listener = match TcpListener::bind((Ipv6Addr::LOCALHOST, 0)) {
+            Ok(listener) => listener,
+            Err(_) => return
+        };
+
+        let port = listener.local_addr().unwrap().port();
+        let addresses = [IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)];
+
+        assert!(is_port_available(&[IpAddr::V4(Ipv4Addr::LOCALHOST)], port));
+        assert!(!is_port_available(&addresses, port));
+    }
+
+    #[test]
+    fn expect_to_guess_mode() {
+        assert_eq!(guess_mode(Some("http-metrics"), 9090), Mode::Http);
+        assert_eq!(guess_mode(Some("https"), 443), Mode::Tcp);
+        assert_eq!(guess_mode(Some("postgres"), 8080), Mode::Tcp);
+        assert_eq!(guess_mode(None, 8080), Mode::Http);
+        assert_eq!(guess_mode(None, 5432), Mode::Tcp);
+    }
+
+    #[test]
+    fn expect_to_not_parse_wrong_port() {
+        assert!(PortArg::from_str("http:foo").is_err());
+        assert!(PortArg::from_str("99999").is_err());
+        assert!(PortArg::from_str("http_port").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::sync::watch;
+use super::stats::Stats;
+use crate::{daemon::logger, error::KubeErr, events::{self, Event, ErrorInfo}};
+
+// Constant
+const REMOTE_ERROR_DELAY: Duration = Duration::from_millis(250);
+
+// Error reported by the pod on the error channel of a port-forward stream (e.g: nothing is listening
+// on the port within the pod)
+#[derive(Clone)]
+pub struct RemoteError {
+    pod: String,
+    port: u16,
+    rx: watch::Receiver<Option<String>>
+}
+
+impl RemoteError {
+    /// Listen to the error channel of a port-forward stream. The error is logged and added to the
+    /// statistics as soon as it's received
+    ///
+    /// # Arguments
+    /// * `channel` - F
+    /// * `pod` - String
+    /// * `port` - u16
+    /// * `stats` - Arc<Stats>
+    pub fn listen<F>(channel: F, pod: String, port: u16, stats: Arc<Stats>) -> RemoteError where
+        F: Future<Output = Option<String>> + Send + 'static
+    {
+        let (tx, rx) = watch::channel(None);
+        let remote = RemoteError { pod, port, rx };
+
+        let (pod, port) = (remote.pod.clone(), remote.port);
+        logger::spawn(async move {
+            if let Some(msg) = channel.await {
+                let err = KubeErr::RemotePort(pod.clone(), port, msg.clone());
+                log::error!("{err}");
+                stats.push_error(err.to_string());
+                events::emit(Event::ForwarderError { pod, port, error: ErrorInfo::from(&err) });
+                let _ = tx.send(Some(msg));
+            }
+        });
+
+        remote
+    }
+
+    /// Get the error reported by the pod. As the error may be received after the stream has been closed,
+    /// the error is awaited for a short delay
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    pub async fn get(&mut self) -> Option<KubeErr> {
+        self.wait(REMOTE_ERROR_DELAY).await
+    }
+
+    /// Wait for the error reported by the pod up to the given delay
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `delay` - Duration
+    pub async fn wait(&mut self, delay: Duration) -> Option<KubeErr> {
+        if self.rx.borrow().is_none() {
+            let _ = tokio::time::timeout(delay, self.rx.changed()).await;
+        }
+
+        self.try_get()
+    }
+
+    /// Get the error reported by the pod without waiting
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn try_get(&self) -> Option<KubeErr> {
+        self.rx
+            .borrow()
+            .clone()
+            .map(|msg| KubeErr::RemotePort(self.pod.clone(), self.port, msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn expect_to_get_remote_error() {
+        let channel = async { Some("connection refused".to_owned()) };
+        let mut remote = RemoteError::listen(channel, "api-1".to_owned(), 8080, Arc::new(Stats::default()));
+        let err = remote.get().await.unwrap();
+
+        assert_eq!(err.kind(), "remote_port");
+        assert!(err.to_string().contains("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn expect_to_not_get_remote_error_when_closed() {
+        let channel = async { None };
+        let mut remote = RemoteError::listen(channel, "api-1".to_owned(), 8080, Arc::new(Stats::default()));
+
+        assert!(remote.get().await.is_none());
+    }
+}
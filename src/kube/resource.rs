@@ -0,0 +1,62 @@
+use std::str::FromStr;
+use super::workload::WorkloadRef;
+use crate::error::KubeErr;
+
+// Reference to a resource which can be forwarded with the format kind/name (e.g: pod/api-1, svc/api, deploy/api)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceRef {
+    Pod(String),
+    Service(String),
+    Workload(WorkloadRef)
+}
+
+impl FromStr for ResourceRef {
+    type Err = KubeErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((kind, name)) if !name.is_empty() => match kind.to_lowercase().as_str() {
+                "po" | "pod" | "pods" => Ok(ResourceRef::Pod(name.to_owned())),
+                "svc" | "service" | "services" => Ok(ResourceRef::Service(name.to_owned())),
+                _ => Ok(ResourceRef::Workload(s.parse()?))
+            },
+            _ => Err(KubeErr::WrongWorkload(s.to_owned()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kube::workload::WorkloadKind;
+
+    #[test]
+    fn expect_to_parse_pod_ref() {
+        let resource = ResourceRef::from_str("pod/api-1").unwrap();
+
+        assert_eq!(resource, ResourceRef::Pod("api-1".to_owned()));
+    }
+
+    #[test]
+    fn expect_to_parse_service_ref() {
+        let resource = ResourceRef::from_str("svc/api").unwrap();
+
+        assert_eq!(resource, ResourceRef::Service("api".to_owned()));
+    }
+
+    #[test]
+    fn expect_to_parse_workload_ref() {
+        let resource = ResourceRef::from_str("deploy/api").unwrap();
+
+        assert_eq!(resource, ResourceRef::Workload(WorkloadRef {
+            kind: WorkloadKind::Deployment,
+            name: "api".to_owned()
+        }));
+    }
+
+    #[test]
+    fn expect_to_not_parse_wrong_ref() {
+        assert!(ResourceRef::from_str("api").is_err());
+        assert!(ResourceRef::from_str("configmap/api").is_err());
+    }
+}
@@ -0,0 +1,347 @@
+use kube::{
+    api::{Api, ListParams},
+    Client
+};
+use k8s_openapi::{
+    api::{
+        core::v1::{Service, Endpoints, ServicePort, ObjectReference},
+        discovery::v1::EndpointSlice
+    },
+    apimachinery::pkg::util::intstr::IntOrString
+};
+use super::{listener::Mode, port::{guess_mode, DeclaredPort}};
+use crate::error::KubeErr;
+
+// Constant
+const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+
+// Struct used to improve work on a service
+pub struct ServiceWrapper {
+    service: Service
+}
+
+impl ServiceWrapper {
+    /// Create a new ServiceWrapper by retrieving the service
+    ///
+    /// # Arguments
+    /// * `client` - Client
+    /// * `ns` - &str
+    /// * `name` - &str
+    pub async fn new(client: Client, ns: &str, name: &str) -> Result<ServiceWrapper, KubeErr> {
+        let service = Api::<Service>::namespaced(client, ns).get(name).await?;
+
+        Ok(ServiceWrapper { service })
+    }
+
+    /// Get a list of port exposed by the service along with their name and their protocol
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn get_ports(&self) -> Vec<DeclaredPort> {
+        self.get_service_ports()
+            .into_iter()
+            .map(|p| DeclaredPort::new(p.port, p.name, p.protocol))
+            .collect()
+    }
+
+    /// Get the port number of a named port of the service
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `name` - &str
+    pub fn get_port_by_name(&self, name: &str) -> Option<i32> {
+        self.get_service_ports()
+            .into_iter()
+            .find(|p| p.name.as_deref() == Some(name))
+            .map(|p| p.port)
+    }
+
+    /// Get the label selector of the service (e.g: app=api,tier=web)
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn get_selector(&self) -> Option<String> {
+        let selector = self.service.spec
+            .as_ref()
+            .and_then(|spec| spec.selector.as_ref())?;
+
+        let labels: Vec<_> = selector
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect();
+
+        match labels.is_empty() {
+            true => None,
+            false => Some(labels.join(","))
+        }
+    }
+
+    /// Get the target port of the service port. The target port is the same as the port
+    /// when it's not specified. A named target port needs to be resolved with the ports of the pod
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `port` - i32
+    pub fn get_target_port(&self, port: i32) -> Option<IntOrString> {
+        let service_port = self.find_port(port)?;
+        let target_port = service_port.target_port
+            .clone()
+            .unwrap_or(IntOrString::Int(service_port.port));
+
+        Some(target_port)
+    }
+
+    /// Guess the mode to use for the given port of the service
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `port` - i32
+    pub fn get_mode_for_port(&self, port: i32) -> Mode {
+        let service_port = self.find_port(port);
+        let name = service_port.and_then(|p| p.app_protocol.as_deref().or(p.name.as_deref()));
+
+        guess_mode(name, port)
+    }
+
+    /// Get the ports of the service
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    fn get_service_ports(&self) -> Vec<ServicePort> {
+        self.service.spec
+            .as_ref()
+            .and_then(|spec| spec.ports.clone())
+            .unwrap_or_default()
+    }
+
+    /// Find the service port matching the given port
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `port` - i32
+    fn find_port(&self, port: i32) -> Option<&ServicePort> {
+        self.service.spec
+            .as_ref()
+            .and_then(|spec| spec.ports.as_ref())
+            .and_then(|ports| ports.iter().find(|p| p.port == port))
+    }
+}
+
+/// Get a list of service's name in the namespace
+///
+/// # Arguments
+/// * `client` - Client
+/// * `ns` - &str
+pub async fn get_service_name_list(client: Client, ns: &str) -> Result<Vec<String>, KubeErr> {
+    let services = Api::<Service>::namespaced(client, ns)
+        .list(&ListParams::default())
+        .await?;
+
+    let names = services.items
+        .into_iter()
+        .filter_map(|s| s.metadata.name)
+        .collect();
+
+    Ok(names)
+}
+
+/// Get the name of a ready pod backing the service by looking at the endpoint slices of the service.
+/// The legacy endpoints are used when the cluster does not serve endpoint slices as they may be
+/// truncated (e.g: more than 1000 endpoints)
+///
+/// # Arguments
+/// * `client` - Client
+/// * `ns` - &str
+/// * `name` - &str
+pub async fn get_ready_endpoint_pod(client: Client, ns: &str, name: &str) -> Result<Option<String>, KubeErr> {
+    let params = ListParams::default().labels(&format!("{SERVICE_NAME_LABEL}={name}"));
+    match Api::<EndpointSlice>::namespaced(client.clone(), ns).list(&params).await {
+        Ok(slices) if !slices.items.is_empty() => return Ok(find_ready_slice_pod(&slices.items)),
+        Ok(_) => log::debug!("No endpoint slice found for the service {name}, using the endpoints"),
+        Err(err) => log::debug!("Unable to list the endpoint slices of the service {name}: {err}")
+    }
+
+    let endpoints = Api::<Endpoints>::namespaced(client, ns).get(name).await?;
+
+    Ok(find_ready_pod(&endpoints))
+}
+
+/// Find the first pod which is listed within the ready endpoints of the endpoint slices. An endpoint
+/// without the ready condition is considered as ready
+///
+/// # Arguments
+/// * `slices` - &[EndpointSlice]
+fn find_ready_slice_pod(slices: &[EndpointSlice]) -> Option<String> {
+    let targets = slices
+        .iter()
+        .flat_map(|slice| slice.endpoints.iter())
+        .filter(|endpoint| endpoint.conditions
+            .as_ref()
+            .and_then(|conditions| conditions.ready)
+            .unwrap_or(true)
+        )
+        .filter_map(|endpoint| endpoint.target_ref.as_ref());
+
+    find_pod_reference(targets)
+}
+
+/// Find the first pod which is listed within the ready addresses of the endpoints
+///
+/// # Arguments
+/// * `endpoints` - &Endpoints
+fn find_ready_pod(endpoints: &Endpoints) -> Option<String> {
+    let targets = endpoints.subsets
+        .iter()
+        .flatten()
+        .flat_map(|subset| subset.addresses.iter().flatten())
+        .filter_map(|address| address.target_ref.as_ref());
+
+    find_pod_reference(targets)
+}
+
+/// Get the name of the first reference which is a pod
+///
+/// # Arguments
+/// * `targets` - I
+fn find_pod_reference<'a, I>(mut targets: I) -> Option<String> where
+    I: Iterator<Item = &'a ObjectReference>
+{
+    targets
+        .find(|target| target.kind.as_deref() == Some("Pod"))
+        .and_then(|target| target.name.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use k8s_openapi::api::{
+        core::v1::{ServiceSpec, EndpointSubset, EndpointAddress},
+        discovery::v1::{Endpoint, EndpointConditions}
+    };
+
+    use super::*;
+
+    fn setup() -> ServiceWrapper {
+        let service = Service {
+            spec: Some(ServiceSpec {
+                selector: Some(BTreeMap::from([
+                    ("app".to_owned(), "api".to_owned())
+                ])),
+                ports: Some(vec![
+                    ServicePort {
+                        name: Some("http".to_owned()),
+                        port: 80,
+                        target_port: Some(IntOrString::String("web".to_owned())),
+                        ..Default::default()
+                    },
+                    ServicePort {
+                        name: Some("postgres".to_owned()),
+                        port: 5432,
+                        ..Default::default()
+                    }
+                ]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        ServiceWrapper { service }
+    }
+
+    fn address(kind: &str, name: &str) -> EndpointAddress {
+        EndpointAddress {
+            ip: "10.0.0.1".to_owned(),
+            target_ref: Some(ObjectReference {
+                kind: Some(kind.to_owned()),
+                name: Some(name.to_owned()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn expect_to_get_service_ports() {
+        let service = setup();
+
+        let ports: Vec<_> = service.get_ports().into_iter().map(|p| p.port).collect();
+
+        assert_eq!(ports, vec![80, 5432]);
+        assert_eq!(service.get_port_by_name("http"), Some(80));
+        assert!(service.get_port_by_name("grpc").is_none());
+    }
+
+    #[test]
+    fn expect_to_get_selector() {
+        let service = setup();
+
+        assert_eq!(service.get_selector().unwrap(), "app=api");
+    }
+
+    #[test]
+    fn expect_to_get_target_port() {
+        let service = setup();
+
+        assert_eq!(service.get_target_port(80).unwrap(), IntOrString::String("web".to_owned()));
+        assert_eq!(service.get_target_port(5432).unwrap(), IntOrString::Int(5432));
+        assert!(service.get_target_port(443).is_none());
+    }
+
+    #[test]
+    fn expect_to_get_mode_for_service_port() {
+        let service = setup();
+
+        assert_eq!(service.get_mode_for_port(80), Mode::Http);
+        assert_eq!(service.get_mode_for_port(5432), Mode::Tcp);
+    }
+
+    #[test]
+    fn expect_to_find_ready_pod() {
+        let endpoints = Endpoints {
+            subsets: Some(vec![EndpointSubset {
+                addresses: Some(vec![address("Node", "node-1"), address("Pod", "api-1")]),
+                not_ready_addresses: Some(vec![address("Pod", "api-2")]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        assert_eq!(find_ready_pod(&endpoints).unwrap(), "api-1");
+    }
+
+    #[test]
+    fn expect_to_find_ready_slice_pod() {
+        let endpoint = |kind: &str, name: &str, ready: Option<bool>| Endpoint {
+            conditions: Some(EndpointConditions { ready, ..Default::default() }),
+            target_ref: address(kind, name).target_ref,
+            ..Default::default()
+        };
+
+        let slices = vec![
+            EndpointSlice {
+                endpoints: vec![endpoint("Pod", "api-2", Some(false)), endpoint("Node", "node-1", None)],
+                ..Default::default()
+            },
+            EndpointSlice {
+                endpoints: vec![endpoint("Pod", "api-3", None)],
+                ..Default::default()
+            }
+        ];
+
+        assert_eq!(find_ready_slice_pod(&slices).unwrap(), "api-3");
+        assert!(find_ready_slice_pod(&slices[..1]).is_none());
+    }
+
+    #[test]
+    fn expect_to_not_find_ready_pod() {
+        let endpoints = Endpoints {
+            subsets: Some(vec![EndpointSubset {
+                not_ready_addresses: Some(vec![address("Pod", "api-2")]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+
+        assert!(find_ready_pod(&endpoints).is_none());
+    }
+}
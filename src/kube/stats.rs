@@ -0,0 +1,245 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex, atomic::{AtomicU16, AtomicU64, Ordering}},
+    task::{Context, Poll}
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// Constant
+const MAX_ERRORS: usize = 5;
+
+// Status of the forward of a port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Status {
+    #[default]
+    Connecting,
+    Up,
+    Reconnecting,
+    Failed,
+    Stopped
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Connecting => write!(f, "connecting"),
+            Status::Up => write!(f, "up"),
+            Status::Reconnecting => write!(f, "reconnecting"),
+            Status::Failed => write!(f, "failed"),
+            Status::Stopped => write!(f, "stopped")
+        }
+    }
+}
+
+// Statistics of the forward of a port. The statistics are shared between the listeners and
+// the connections of the port
+#[derive(Debug, Default)]
+pub struct Stats {
+    status: Mutex<Status>,
+    local_port: AtomicU16,
+    connections: AtomicU64,
+    bytes: AtomicU64,
+    errors: Mutex<VecDeque<String>>
+}
+
+impl Stats {
+    /// Set the status of the forward
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `status` - Status
+    pub fn set_status(&self, status: Status) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status;
+        }
+    }
+
+    /// Get the status of the forward
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn status(&self) -> Status {
+        self.status
+            .lock()
+            .map(|status| *status)
+            .unwrap_or_default()
+    }
+
+    /// Set the local port bound by the listeners
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `port` - u16
+    pub fn set_local_port(&self, port: u16) {
+        self.local_port.store(port, Ordering::Relaxed);
+    }
+
+    /// Get the local port bound by the listeners. None is returned when the listeners are not bound yet
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn local_port(&self) -> Option<u16> {
+        match self.local_port.load(Ordering::Relaxed) {
+            0 => None,
+            port => Some(port)
+        }
+    }
+
+    /// Count a new connection accepted by the listeners
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn add_connection(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the number of connections accepted by the listeners
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn connections(&self) -> u64 {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    /// Count the bytes transferred between the local machine and the pod
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `bytes` - u64
+    pub fn add_bytes(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Get the number of bytes transferred between the local machine and the pod
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Keep an error of the forward. Only the most recent errors are kept
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `err` - String
+    pub fn push_error(&self, err: String) {
+        if let Ok(mut errors) = self.errors.lock() {
+            errors.push_back(err);
+            if errors.len() > MAX_ERRORS {
+                errors.pop_front();
+            }
+        }
+    }
+
+    /// Get the most recent errors of the forward, the oldest first
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn errors(&self) -> Vec<String> {
+        self.errors
+            .lock()
+            .map(|errors| errors.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+// Stream counting the bytes read and written in the statistics as the data flows
+pub struct CountingStream<S> {
+    inner: S,
+    stats: Arc<Stats>
+}
+
+impl<S> CountingStream<S> {
+    /// Create a new CountingStream
+    ///
+    /// # Arguments
+    /// * `inner` - S
+    /// * `stats` - Arc<Stats>
+    pub fn new(inner: S, stats: Arc<Stats>) -> CountingStream<S> {
+        CountingStream { inner, stats }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CountingStream<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        let res = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = res {
+            self.stats.add_bytes((buf.filled().len() - filled) as u64);
+        }
+
+        res
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for CountingStream<S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let res = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = res {
+            self.stats.add_bytes(written as u64);
+        }
+
+        res
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use super::*;
+
+    #[test]
+    fn expect_to_count_traffic() {
+        let stats = Stats::default();
+        stats.add_connection();
+        stats.add_connection();
+        stats.add_bytes(128);
+
+        assert_eq!(stats.connections(), 2);
+        assert_eq!(stats.bytes(), 128);
+        assert_eq!(stats.status(), Status::Connecting);
+        assert!(stats.local_port().is_none());
+    }
+
+    #[tokio::test]
+    async fn expect_to_count_bytes_as_they_flow() {
+        let stats = Arc::new(Stats::default());
+        let (local, mut remote) = tokio::io::duplex(64);
+        let mut stream = CountingStream::new(local, stats.clone());
+
+        stream.write_all(b"ping").await.unwrap();
+        assert_eq!(stats.bytes(), 4);
+
+        let mut buf = [0; 4];
+        remote.read_exact(&mut buf).await.unwrap();
+        remote.write_all(b"pong!").await.unwrap();
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).await.unwrap();
+
+        assert_eq!(stats.bytes(), 9);
+    }
+
+    #[test]
+    fn expect_to_keep_recent_errors() {
+        let stats = Stats::default();
+        for i in 0..MAX_ERRORS + 2 {
+            stats.push_error(i.to_string());
+        }
+
+        let errors = stats.errors();
+        assert_eq!(errors.len(), MAX_ERRORS);
+        assert_eq!(errors[0], "2");
+    }
+}
@@ -0,0 +1,342 @@
+use std::{fmt, str::FromStr, fmt::Debug};
+use kube::{
+    api::{Api, ListParams},
+    Client,
+    Resource,
+    ResourceExt
+};
+use k8s_openapi::{
+    api::{
+        apps::v1::{Deployment, StatefulSet, DaemonSet, ReplicaSet},
+        batch::v1::Job
+    },
+    apimachinery::pkg::apis::meta::v1::{LabelSelector, OwnerReference},
+    serde::de::DeserializeOwned
+};
+use serde::{de, Serialize, Serializer, Deserialize, Deserializer};
+use crate::error::KubeErr;
+
+// Constant
+// Kind of the owner of the pods of a deployment
+pub const REPLICA_SET: &str = "ReplicaSet";
+
+// Kind of workload which owns a set of pods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkloadKind {
+    Deployment,
+    StatefulSet,
+    DaemonSet,
+    ReplicaSet,
+    Job
+}
+
+pub const WORKLOAD_KINDS: [WorkloadKind; 5] = [
+    WorkloadKind::Deployment,
+    WorkloadKind::StatefulSet,
+    WorkloadKind::DaemonSet,
+    WorkloadKind::ReplicaSet,
+    WorkloadKind::Job
+];
+
+impl FromStr for WorkloadKind {
+    type Err = KubeErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "deploy" | "deployment" | "deployments" => Ok(WorkloadKind::Deployment),
+            "sts" | "statefulset" | "statefulsets" => Ok(WorkloadKind::StatefulSet),
+            "ds" | "daemonset" | "daemonsets" => Ok(WorkloadKind::DaemonSet),
+            "rs" | "replicaset" | "replicasets" => Ok(WorkloadKind::ReplicaSet),
+            "job" | "jobs" => Ok(WorkloadKind::Job),
+            _ => Err(KubeErr::WrongWorkload(s.to_owned()))
+        }
+    }
+}
+
+impl fmt::Display for WorkloadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkloadKind::Deployment => write!(f, "deployment"),
+            WorkloadKind::StatefulSet => write!(f, "statefulset"),
+            WorkloadKind::DaemonSet => write!(f, "daemonset"),
+            WorkloadKind::ReplicaSet => write!(f, "replicaset"),
+            WorkloadKind::Job => write!(f, "job")
+        }
+    }
+}
+
+// Reference to a workload with the format kind/name (e.g: deploy/api)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkloadRef {
+    pub kind: WorkloadKind,
+    pub name: String
+}
+
+impl FromStr for WorkloadRef {
+    type Err = KubeErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((kind, name)) if !name.is_empty() => Ok(WorkloadRef {
+                kind: kind.parse()?,
+                name: name.to_owned()
+            }),
+            _ => Err(KubeErr::WrongWorkload(s.to_owned()))
+        }
+    }
+}
+
+impl fmt::Display for WorkloadRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.kind, self.name)
+    }
+}
+
+impl Serialize for WorkloadRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for WorkloadRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+// Workload resolved with the cluster. The owners are the uid of the resources which directly own the pods
+// (e.g: the ReplicaSets of a Deployment)
+#[derive(Debug, Clone)]
+pub struct ResolvedWorkload {
+    pub selector: String,
+    pub owners: Vec<String>
+}
+
+impl WorkloadRef {
+    /// Resolve the label selector of the workload and the owners of its pods
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `client` - Client
+    /// * `ns` - &str
+    pub async fn resolve(&self, client: Client, ns: &str) -> Result<ResolvedWorkload, KubeErr> {
+        let (selector, uid) = match self.kind {
+            WorkloadKind::Deployment => get_workload::<Deployment>(client.clone(), ns, &self.name, |d| {
+                d.spec.as_ref().map(|spec| spec.selector.clone())
+            }).await?,
+            WorkloadKind::StatefulSet => get_workload::<StatefulSet>(client.clone(), ns, &self.name, |s| {
+                s.spec.as_ref().map(|spec| spec.selector.clone())
+            }).await?,
+            WorkloadKind::DaemonSet => get_workload::<DaemonSet>(client.clone(), ns, &self.name, |d| {
+                d.spec.as_ref().map(|spec| spec.selector.clone())
+            }).await?,
+            WorkloadKind::ReplicaSet => get_workload::<ReplicaSet>(client.clone(), ns, &self.name, |r| {
+                r.spec.as_ref().map(|spec| spec.selector.clone())
+            }).await?,
+            WorkloadKind::Job => get_workload::<Job>(client.clone(), ns, &self.name, |j| {
+                j.spec.as_ref().and_then(|spec| spec.selector.clone())
+            }).await?
+        };
+
+        let selector = selector
+            .and_then(|selector| selector_to_string(&selector))
+            .ok_or_else(|| KubeErr::EmptySelector(self.to_string()))?;
+
+        // pods of a deployment are owned by the replicasets of the deployment
+        let owners = match self.kind {
+            WorkloadKind::Deployment => {
+                let replicasets = Api::<ReplicaSet>::namespaced(client, ns)
+                    .list(&ListParams::default().labels(&selector))
+                    .await?;
+
+                replicasets.items
+                    .iter()
+                    .filter(|rs| is_owned_by(rs.owner_references(), std::slice::from_ref(&uid)))
+                    .filter_map(|rs| rs.uid())
+                    .collect()
+            },
+            _ => vec![uid]
+        };
+
+        Ok(ResolvedWorkload { selector, owners })
+    }
+}
+
+/// Get a list of workload's name of the given kind in the namespace
+///
+/// # Arguments
+/// * `client` - Client
+/// * `ns` - &str
+/// * `kind` - WorkloadKind
+pub async fn get_workload_name_list(client: Client, ns: &str, kind: WorkloadKind) -> Result<Vec<String>, KubeErr> {
+    match kind {
+        WorkloadKind::Deployment => list_names(Api::<Deployment>::namespaced(client, ns)).await,
+        WorkloadKind::StatefulSet => list_names(Api::<StatefulSet>::namespaced(client, ns)).await,
+        WorkloadKind::DaemonSet => list_names(Api::<DaemonSet>::namespaced(client, ns)).await,
+        WorkloadKind::ReplicaSet => list_names(Api::<ReplicaSet>::namespaced(client, ns)).await,
+        WorkloadKind::Job => list_names(Api::<Job>::namespaced(client, ns)).await
+    }
+}
+
+/// Check whether a resource is owned by one of the given owners
+///
+/// # Arguments
+/// * `owner_references` - &[OwnerReference]
+/// * `owners` - &[String]
+pub fn is_owned_by(owner_references: &[OwnerReference], owners: &[String]) -> bool {
+    owner_references
+        .iter()
+        .any(|reference| owners.contains(&reference.uid))
+}
+
+/// Get a workload and return its label selector and its uid
+///
+/// # Arguments
+/// * `client` - Client
+/// * `ns` - &str
+/// * `name` - &str
+/// * `selector` - fn(&K) -> Option<LabelSelector>
+async fn get_workload<K>(
+    client: Client,
+    ns: &str,
+    name: &str,
+    selector: fn(&K) -> Option<LabelSelector>
+) -> Result<(Option<LabelSelector>, String), KubeErr> where
+    K: Resource<DynamicType = ()> + Clone + DeserializeOwned + Debug
+{
+    let workload = Api::<K>::namespaced(client, ns).get(name).await?;
+    let uid = workload.uid().unwrap_or_default();
+
+    Ok((selector(&workload), uid))
+}
+
+/// List the name of the resources of the given api
+///
+/// # Arguments
+/// * `api` - Api<K>
+async fn list_names<K>(api: Api<K>) -> Result<Vec<String>, KubeErr> where
+    K: Resource<DynamicType = ()> + Clone + DeserializeOwned + Debug
+{
+    let list = api.list(&ListParams::default()).await?;
+    let names = list.items
+        .iter()
+        .map(|item| item.name())
+        .collect();
+
+    Ok(names)
+}
+
+/// Convert a label selector to the string format used by the Kubernetes API (e.g: app=api,tier in (web))
+///
+/// # Arguments
+/// * `selector` - &LabelSelector
+pub fn selector_to_string(selector: &LabelSelector) -> Option<String> {
+    let mut requirements: Vec<String> = selector.match_labels
+        .iter()
+        .flatten()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+
+    for expr in selector.match_expressions.iter().flatten() {
+        let values = expr.values.clone().unwrap_or_default().join(",");
+        let requirement = match expr.operator.as_str() {
+            "In" => format!("{} in ({values})", expr.key),
+            "NotIn" => format!("{} notin ({values})", expr.key),
+            "Exists" => expr.key.clone(),
+            "DoesNotExist" => format!("!{}", expr.key),
+            _ => continue
+        };
+
+        requirements.push(requirement);
+    }
+
+    match requirements.is_empty() {
+        true => None,
+        false => Some(requirements.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelectorRequirement;
+
+    use super::*;
+
+    #[test]
+    fn expect_to_parse_workload_ref() {
+        let workload = WorkloadRef::from_str("deploy/api").unwrap();
+
+        assert_eq!(workload.kind, WorkloadKind::Deployment);
+        assert_eq!(workload.name, "api");
+        assert_eq!(workload.to_string(), "deployment/api");
+    }
+
+    #[test]
+    fn expect_to_parse_job_ref() {
+        let workload = WorkloadRef::from_str("job/migrate").unwrap();
+
+        assert_eq!(workload.kind, WorkloadKind::Job);
+    }
+
+    #[test]
+    fn expect_to_serialize_workload_ref() {
+        let workload = WorkloadRef::from_str("deploy/api").unwrap();
+        let json = serde_json::to_string(&workload).unwrap();
+
+        assert_eq!(json, "\"deployment/api\"");
+        assert_eq!(serde_json::from_str::<WorkloadRef>(&json).unwrap(), workload);
+        assert!(serde_json::from_str::<WorkloadRef>("\"api\"").is_err());
+    }
+
+    #[test]
+    fn expect_to_check_owner() {
+        let references = vec![OwnerReference {
+            kind: "ReplicaSet".to_owned(),
+            name: "api-5d4f".to_owned(),
+            uid: "1234".to_owned(),
+            ..Default::default()
+        }];
+
+        assert!(is_owned_by(&references, &["1234".to_owned()]));
+        assert!(!is_owned_by(&references, &["5678".to_owned()]));
+        assert!(!is_owned_by(&[], &["1234".to_owned()]));
+    }
+
+    #[test]
+    fn expect_to_not_parse_wrong_workload_ref() {
+        assert!(WorkloadRef::from_str("api").is_err());
+        assert!(WorkloadRef::from_str("foo/api").is_err());
+        assert!(WorkloadRef::from_str("sts/").is_err());
+    }
+
+    #[test]
+    fn expect_to_convert_selector() {
+        let selector = LabelSelector {
+            match_labels: Some(BTreeMap::from([
+                ("app".to_owned(), "api".to_owned())
+            ])),
+            match_expressions: Some(vec![
+                LabelSelectorRequirement {
+                    key: "tier".to_owned(),
+                    operator: "In".to_owned(),
+                    values: Some(vec!["web".to_owned(), "backend".to_owned()])
+                },
+                LabelSelectorRequirement {
+                    key: "canary".to_owned(),
+                    operator: "DoesNotExist".to_owned(),
+                    values: None
+                }
+            ])
+        };
+
+        assert_eq!(selector_to_string(&selector).unwrap(), "app=api,tier in (web,backend),!canary");
+    }
+
+    #[test]
+    fn expect_to_not_convert_empty_selector() {
+        assert!(selector_to_string(&LabelSelector::default()).is_none());
+    }
+}
@@ -1,48 +1,381 @@
-use clap::Parser;
+use clap::{CommandFactory, ErrorKind, Parser, Subcommand};
 
 mod scenario;
 mod error;
 mod kube;
 mod utils;
+mod session;
+mod config;
+mod history;
+mod daemon;
+mod dashboard;
+mod events;
+mod registry;
+mod exec;
+mod env;
+
+use session::Session;
+use scenario::forward::ForwardArgs;
+use kube::resource::ResourceRef;
+use error::KubeErr;
+use session::target::Target;
+
+// Constant
+const ERROR_EXIT_CODE: i32 = 1;
+const TUI_JSON_CONFLICT: &str = "The dashboard can't be used along with the JSON output";
 
 #[derive(Debug, Parser)]
 #[clap(name = "kubeforward", author = "marc intha-amnouay")]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    #[clap(long)]
+    tui: bool,
+
+    #[clap(short, long, arg_enum, default_value = "text")]
+    output: events::OutputFormat,
+
+    #[clap(long)]
+    env_file: Option<std::path::PathBuf>,
+
+    #[clap(long, arg_enum, default_value = "dotenv")]
+    env_format: env::EnvFormat,
+
+    #[clap(long)]
+    env_name: Option<String>,
+
+    #[clap(flatten)]
+    forward: ForwardOpts
+}
+
+#[derive(Debug, clap::Args)]
+struct ForwardOpts {
+    #[clap(conflicts_with_all = &["pod", "selector", "workload", "service"])]
+    resource: Option<ResourceRef>,
+
     #[clap(short, long)]
     namespace: Option<String>,
 
     #[clap(short, long)]
-    context: bool
+    context: Option<Option<String>>,
+
+    #[clap(long)]
+    pod: Option<String>,
+
+    #[clap(long)]
+    container: Option<String>,
+
+    #[clap(short, long, arg_enum)]
+    mode: Option<kube::listener::Mode>,
+
+    #[clap(short, long, multiple_occurrences = true)]
+    port: Vec<kube::port::PortArg>,
+
+    #[clap(short, long, multiple_occurrences = true, default_value = "localhost")]
+    address: Vec<String>,
+
+    #[clap(short = 'l', long, conflicts_with_all = &["workload", "pod"])]
+    selector: Option<String>,
+
+    #[clap(short, long, conflicts_with = "pod")]
+    workload: Option<kube::workload::WorkloadRef>,
+
+    #[clap(short, long, conflicts_with_all = &["selector", "workload", "pod"])]
+    service: Option<String>,
+
+    #[clap(long)]
+    port_range: Option<registry::PortRange>,
+
+    #[clap(long)]
+    health: Option<String>,
+
+    #[clap(long)]
+    wait: bool,
+
+    #[clap(long, default_value = "60")]
+    wait_timeout: u64
+}
+
+impl ForwardOpts {
+    /// Get how long to wait for a ready pod. None is returned when waiting is not enabled
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    fn wait(&self) -> Option<std::time::Duration> {
+        match self.wait {
+            true => Some(std::time::Duration::from_secs(self.wait_timeout)),
+            false => None
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)]
+enum Command {
+    /// Start the forward declared by a profile of the configuration files
+    Up {
+        profile: String
+    },
+    /// Replay the last forward
+    Last,
+    /// Select a forward to replay from the history
+    History,
+    /// Run the forwards in the background behind a control socket
+    Daemon,
+    /// Start a forward in the background daemon
+    Start {
+        #[clap(flatten)]
+        forward: ForwardOpts
+    },
+    /// List the forwards of the daemon
+    Ls,
+    /// Stop a forward of the daemon
+    Stop {
+        id: u32
+    },
+    /// Print the logs of a forward of the daemon
+    Logs {
+        id: u32
+    },
+    /// Stop the daemon along with its forwards
+    Shutdown,
+    /// Run a command with the forwards active and return its exit code
+    Exec {
+        #[clap(flatten)]
+        forward: ForwardOpts,
+
+        #[clap(last = true, required = true)]
+        command: Vec<String>
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    // init the logger
-    simple_logger::init_with_level(log::Level::Info)
-        .expect("Expect to initialize the logger");
-
     let args = Args::parse();
-    let context_scenario = match args.context {
-        false => None,
-        true => {
-            let config = scenario::context::trigger_scenario()
-                .expect("Expect to retrieve a context");
-            Some(config)
+    // the events would be printed over the dashboard
+    if args.tui && args.output == events::OutputFormat::Json {
+        Args::command().error(ErrorKind::ArgumentConflict, TUI_JSON_CONFLICT).exit();
+    }
+
+    // init the logger. The daemon keeps the logs of each forward even when the events are printed as JSON
+    let is_daemon = matches!(args.command, Some(Command::Daemon));
+    match is_daemon {
+        true => daemon::logger::init(),
+        false => simple_logger::init_with_level(log::Level::Info)
+    }.expect("Expect to initialize the logger");
+    events::set_output(args.output);
+    if is_daemon {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+
+    // a failure is reported with the exit code so that the scripts can detect it. The exec command uses
+    // a dedicated code to not be mistaken with the exit code of the command
+    let error_code = match args.command {
+        Some(Command::Exec { .. }) => exec::FAILURE_EXIT_CODE,
+        _ => ERROR_EXIT_CODE
+    };
+
+    if let Err(err) = run(args).await {
+        log::error!("{}", err.to_string());
+        events::emit(events::Event::Error { error: events::ErrorInfo::from(&err) });
+        std::process::exit(error_code);
+    }
+}
+
+/// Run the command provided with the CLI
+///
+/// # Arguments
+/// * `args` - Args
+async fn run(args: Args) -> Result<(), KubeErr> {
+    let context = args.forward.context.clone();
+    let mode = args.forward.mode;
+    let env_file = args.env_file
+        .clone()
+        .map(|path| env::EnvFile::new(path, args.env_format, args.env_name.clone()));
+    let target = match args.command {
+        None => {
+            let mut session = build_session(args.forward).await?;
+            session.set_env_file(env_file);
+            return run_session(session, args.tui, &context, mode).await;
+        },
+        Some(Command::Up { profile }) => {
+            let profile = config::Config::load()?.get_profile(&profile)?;
+            let ns = match &profile.namespace {
+                Some(ns) => ns.to_owned(),
+                None => kube::get_default_namespace(&profile.context).await?
+            };
+
+            let mut forward_args = profile.to_forward_args()?;
+            forward_args.addresses = kube::listener::resolve_addresses(&args.forward.address)?;
+            forward_args.mode = forward_args.mode.or(args.forward.mode);
+            forward_args.wait = args.forward.wait();
+            forward_args.health = forward_args.health.or(args.forward.health);
+
+            scenario::forward::trigger_scenario(profile.context, ns, forward_args).await?
+        },
+        Some(Command::Last) => {
+            let target = history::History::load()?.last()?.into_target().await?;
+            history::save_target(&target);
+            target
+        },
+        Some(Command::History) => scenario::history::trigger_scenario().await?,
+        Some(Command::Daemon) => return daemon::run().await,
+        Some(Command::Start { forward }) => {
+            let (targets, addresses) = build_session(forward).await?.into_parts();
+            return daemon::client::start(targets, addresses).await;
+        },
+        Some(Command::Ls) => return daemon::client::list().await,
+        Some(Command::Stop { id }) => return daemon::client::stop(id).await,
+        Some(Command::Logs { id }) => return daemon::client::logs(id).await,
+        Some(Command::Shutdown) => return daemon::client::shutdown().await,
+        Some(Command::Exec { forward, command }) => {
+            let mut session = build_session(forward).await?;
+            session.set_env_file(env_file);
+            let code = exec::run(session, command).await?;
+            std::process::exit(code);
+        }
+    };
+
+    let addresses = kube::listener::resolve_addresses(&args.forward.address)?;
+    let mut session = Session::new(addresses, get_port_range(args.forward.port_range));
+    session
+        .add_target(target)
+        .set_env_file(env_file);
+
+    run_session(session, args.tui, &context, mode).await
+}
+
+/// Run the session either in the terminal or within the dashboard. The forwards added from the dashboard
+/// are prompted like the other targets
+///
+/// # Arguments
+/// * `session` - Session
+/// * `tui` - bool
+/// * `context` - &Option<Option<String>>
+/// * `mode` - Option<Mode>
+async fn run_session(
+    session: Session,
+    tui: bool,
+    context: &Option<Option<String>>,
+    mode: Option<kube::listener::Mode>
+) -> Result<(), KubeErr> {
+    if !tui {
+        session.run().await;
+        return Ok(());
+    }
+
+    let addresses = session.addresses();
+    let mut dashboard = dashboard::Dashboard::new(session);
+    while dashboard.run().await? == dashboard::Action::Add {
+        let args = ForwardArgs { addresses: addresses.clone(), mode, ..Default::default() };
+        match prompt_target(context, None, args).await {
+            Ok(target) => {
+                dashboard.add(target);
+            },
+            Err(err) => log::error!("{}", err.to_string())
+        }
+    }
+
+    dashboard.stop_all();
+
+    Ok(())
+}
+
+/// Build the session of targets to forward. Values which are not provided with the CLI are prompted
+///
+/// # Arguments
+/// * `args` - ForwardOpts
+async fn build_session(args: ForwardOpts) -> Result<Session, KubeErr> {
+    let addresses = kube::listener::resolve_addresses(&args.address)?;
+    let mut session = Session::new(addresses, get_port_range(args.port_range));
+
+    let wait = args.wait();
+    let mut namespace = args.namespace;
+
+    let mut forward_args = ForwardArgs {
+        addresses: session.addresses(),
+        wait,
+        health: args.health,
+        mode: args.mode,
+        ports: args.port,
+        pod: args.pod,
+        container: args.container,
+        selector: args.selector,
+        workload: args.workload,
+        service: args.service
+    };
+
+    match args.resource {
+        Some(ResourceRef::Pod(pod)) => forward_args.pod = Some(pod),
+        Some(ResourceRef::Service(service)) => forward_args.service = Some(service),
+        Some(ResourceRef::Workload(workload)) => forward_args.workload = Some(workload),
+        None => {}
+    };
+
+    // another target is only proposed when the first target has been selected with the prompts
+    let propose_targets = utils::is_interactive() && !forward_args.has_target();
+    let mut forward_args = Some(forward_args);
+
+    // targets are added to the session until the user does not want to forward another pod
+    loop {
+        // the values of the CLI are only used for the first target
+        let target_args = forward_args
+            .take()
+            .unwrap_or(ForwardArgs { addresses: session.addresses(), wait, mode: args.mode, ..Default::default() });
+
+        let target = prompt_target(&args.context, namespace.take(), target_args).await?;
+        session.add_target(target);
+
+        if !propose_targets || !scenario::session::trigger_scenario()? {
+            break;
         }
+    }
+
+    Ok(session)
+}
+
+/// Build a target to forward. The context and the namespace are prompted when they're not provided
+///
+/// # Arguments
+/// * `context` - &Option<Option<String>>
+/// * `namespace` - Option<String>
+/// * `args` - ForwardArgs
+async fn prompt_target(
+    context: &Option<Option<String>>,
+    namespace: Option<String>,
+    args: ForwardArgs
+) -> Result<Target, KubeErr> {
+    let context = match context {
+        None => None,
+        Some(Some(context)) => Some(context.to_owned()),
+        Some(None) => Some(utils::value_or_prompt(None, "--context", scenario::context::trigger_scenario)?)
     };
 
-    let ns = match args.namespace {
+    let ns = match namespace {
         Some(ns) => ns,
-        None => scenario::namespace::trigger_scenario(&context_scenario).await
-            .expect("Expect to retrieve namespace from the list of namespace")
+        None if utils::is_interactive() => scenario::namespace::trigger_scenario(&context).await?,
+        None => kube::get_default_namespace(&context).await?
     };
 
-    let res = scenario::forward::trigger_scenario(
-        context_scenario,
-        ns
-    ).await;
+    scenario::forward::trigger_scenario(context, ns, args).await
+}
 
-    if let Err(err) = res {
-        log::error!("{}", err.to_string());
+/// Get the range used to assign the auto ports. The range of the CLI takes precedence over the configuration.
+/// A configuration which can't be loaded doesn't prevent the forward and the default range is used
+///
+/// # Arguments
+/// * `port_range` - Option<PortRange>
+fn get_port_range(port_range: Option<registry::PortRange>) -> registry::PortRange {
+    if let Some(range) = port_range {
+        return range;
+    }
+
+    match config::Config::load().and_then(|config| config.get_port_range()) {
+        Ok(range) => range,
+        Err(err) => {
+            log::warn!("{err}, the default port range is used");
+            registry::PortRange::default()
+        }
     }
 }
@@ -0,0 +1,294 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs,
+    net::IpAddr,
+    path::PathBuf,
+    str::FromStr
+};
+use serde::{Serialize, Deserialize};
+use crate::{
+    error::KubeErr,
+    kube::{CURRENT_CONTEXT, port::is_port_available},
+    session::target::Target
+};
+
+// Constant
+const REGISTRY_FILE: &str = "kubeforward/ports.json";
+const DEFAULT_RANGE_START: u16 = 20000;
+const DEFAULT_RANGE_END: u16 = 29999;
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// Range of local ports used to assign the auto ports (e.g: 20000-29999)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16
+}
+
+impl Default for PortRange {
+    fn default() -> Self {
+        PortRange {
+            start: DEFAULT_RANGE_START,
+            end: DEFAULT_RANGE_END
+        }
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = KubeErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| KubeErr::WrongPortRange(s.to_owned()))?;
+
+        let range = PortRange {
+            start: start.trim().parse()?,
+            end: end.trim().parse()?
+        };
+
+        match range.start > 0 && range.start <= range.end {
+            true => Ok(range),
+            false => Err(KubeErr::WrongPortRange(s.to_owned()))
+        }
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl PortRange {
+    /// Check whether the port is within the range
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `port` - u16
+    pub fn contains(&self, port: u16) -> bool {
+        (self.start..=self.end).contains(&port)
+    }
+
+    /// Get the number of ports of the range
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    fn len(&self) -> u64 {
+        (self.end - self.start) as u64 + 1
+    }
+}
+
+// Registry of the local ports assigned to each target so that a target keeps the same local port
+// between sessions
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Registry {
+    ports: BTreeMap<String, u16>
+}
+
+impl Registry {
+    /// Load the registry. An empty registry is returned when no port has been assigned yet
+    pub fn load() -> Result<Registry, KubeErr> {
+        let path = get_registry_path()?;
+        if !path.is_file() {
+            return Ok(Registry::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|err| KubeErr::Registry(err.to_string()))?;
+
+        serde_json::from_str(&content).map_err(|err| KubeErr::Registry(err.to_string()))
+    }
+
+    /// Save the registry
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn save(&self) -> Result<(), KubeErr> {
+        let path = get_registry_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| KubeErr::Registry(err.to_string()))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| KubeErr::Registry(err.to_string()))?;
+
+        fs::write(&path, content).map_err(|err| KubeErr::Registry(err.to_string()))
+    }
+
+    /// Assign a local port to the key. The port registered for the key is reused. Otherwise the port is
+    /// derived from the hash of the key and the next ports are probed when the port is already assigned
+    /// to another key or is not available. A port used only because the registered port is busy
+    /// is not saved
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `key` - &str
+    /// * `range` - PortRange
+    /// * `available` - F
+    pub fn assign<F>(&mut self, key: &str, range: PortRange, available: F) -> Option<u16> where
+        F: Fn(u16) -> bool
+    {
+        let registered = self.ports
+            .get(key)
+            .copied()
+            .filter(|port| range.contains(*port));
+
+        if let Some(port) = registered {
+            if available(port) {
+                return Some(port);
+            }
+        }
+
+        let offset = hash(key) % range.len();
+        let port = (0..range.len())
+            .map(|i| range.start + ((offset + i) % range.len()) as u16)
+            .find(|port| !self.ports.values().any(|p| p == port) && available(*port))?;
+
+        if registered.is_none() {
+            self.ports.insert(key.to_owned(), port);
+        }
+
+        Some(port)
+    }
+}
+
+/// Assign a stable local port to each mapping of the target which use an auto port
+///
+/// # Arguments
+/// * `target` - &mut Target
+/// * `range` - PortRange
+/// * `addresses` - &[IpAddr]
+pub fn assign_ports(target: &mut Target, range: PortRange, addresses: &[IpAddr]) {
+    if target.mappings.iter().all(|m| m.local != 0) {
+        return;
+    }
+
+    let mut registry = match Registry::load() {
+        Ok(registry) => registry,
+        Err(err) => {
+            log::warn!("Unable to load the registry of the ports, the system will pick the ports: {err}");
+            return;
+        }
+    };
+
+    let workload = get_workload_key(target);
+    for mapping in target.mappings.iter_mut().filter(|m| m.local == 0) {
+        let key = format!("{workload}/{}", mapping.remote);
+        match registry.assign(&key, range, |port| is_port_available(addresses, port)) {
+            Some(port) => mapping.local = port,
+            None => log::warn!("No port is available in the range {range} for {key}")
+        }
+    }
+
+    if let Err(err) = registry.save() {
+        log::warn!("Unable to save the registry of the ports: {err}");
+    }
+}
+
+/// Get the key identifying the workload of the target (e.g: prod/default/deployment/api)
+///
+/// # Arguments
+/// * `target` - &Target
+fn get_workload_key(target: &Target) -> String {
+    let context = target.context.as_deref().unwrap_or(CURRENT_CONTEXT);
+
+    format!("{context}/{}/{}", target.namespace, target.workload())
+}
+
+/// Hash the key with FNV-1a which is stable between builds
+///
+/// # Arguments
+/// * `key` - &str
+fn hash(key: &str) -> u64 {
+    key.bytes().fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Get the path of the registry file
+fn get_registry_path() -> Result<PathBuf, KubeErr> {
+    dirs_next::data_dir()
+        .map(|dir| dir.join(REGISTRY_FILE))
+        .ok_or_else(|| KubeErr::Registry("unable to find the data directory".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kube::pod::PodOwner;
+    use super::*;
+
+    fn target(pod: &str, rs: &str) -> Target {
+        Target {
+            context: Some("prod".to_owned()),
+            namespace: "default".to_owned(),
+            pod: pod.to_owned(),
+            owner: Some(PodOwner {
+                kind: "ReplicaSet".to_owned(),
+                name: rs.to_owned(),
+                uid: "1234".to_owned()
+            }),
+            selector: None,
+            workload_ref: None,
+            container: None,
+            mappings: vec![],
+            health: None
+        }
+    }
+
+    #[test]
+    fn expect_to_parse_port_range() {
+        let range = PortRange::from_str("20000-20009").unwrap();
+
+        assert_eq!(range, PortRange { start: 20000, end: 20009 });
+        assert!(range.contains(20009));
+        assert!(!range.contains(20010));
+        assert!(PortRange::from_str("3000").is_err());
+        assert!(PortRange::from_str("3000-2000").is_err());
+    }
+
+    #[test]
+    fn expect_to_assign_same_port() {
+        let range = PortRange { start: 20000, end: 20009 };
+        let mut registry = Registry::default();
+        let port = registry.assign("prod/default/deployment/api/8080", range, |_| true).unwrap();
+
+        assert!(range.contains(port));
+        assert_eq!(registry.assign("prod/default/deployment/api/8080", range, |_| true), Some(port));
+
+        // the same key get the same port with a new registry
+        let mut other = Registry::default();
+        assert_eq!(other.assign("prod/default/deployment/api/8080", range, |_| true), Some(port));
+    }
+
+    #[test]
+    fn expect_to_resolve_collisions() {
+        let range = PortRange { start: 20000, end: 20001 };
+        let mut registry = Registry::default();
+        let first = registry.assign("a", range, |_| true).unwrap();
+        let second = registry.assign("b", range, |_| true).unwrap();
+
+        assert_ne!(first, second);
+        assert!(registry.assign("c", range, |_| true).is_none());
+    }
+
+    #[test]
+    fn expect_to_not_save_temporary_port() {
+        let range = PortRange { start: 20000, end: 20009 };
+        let mut registry = Registry::default();
+        let port = registry.assign("a", range, |_| true).unwrap();
+        let temporary = registry.assign("a", range, |p| p != port).unwrap();
+
+        assert_ne!(port, temporary);
+        assert_eq!(registry.assign("a", range, |_| true), Some(port));
+    }
+
+    #[test]
+    fn expect_to_get_workload_key() {
+        let first = get_workload_key(&target("api-5d4f-abcde", "api-5d4f"));
+        let second = get_workload_key(&target("api-7c8b-fghij", "api-7c8b"));
+
+        assert_eq!(first, "prod/default/deployment/api");
+        assert_eq!(first, second);
+    }
+}
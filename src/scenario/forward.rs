@@ -1,33 +1,359 @@
-use inquire::{Select, Text};
+use std::{fmt, net::IpAddr, time::Duration};
+use inquire::{Select, MultiSelect, Text};
+use kube::{api::{Api, ListParams}, ResourceExt};
+use k8s_openapi::{api::core::v1::Pod, apimachinery::pkg::util::intstr::IntOrString};
 use crate::kube::{
-    pod::PodsList,
+    authenticate_with_cluster,
+    follow,
+    pod::{PodsList, PodOption},
+    listener::Mode,
+    port::{DeclaredPort, PortArg, PortMapping, RemotePort, SuggestedPort, parse_local_port, parse_ports, is_port_available},
+    workload::{self, WorkloadRef, WORKLOAD_KINDS, is_owned_by},
+    service::{self, ServiceWrapper}
 };
+use crate::session::target::Target;
 use crate::error::KubeErr;
+use crate::{utils, history};
 
 // constant
+const KIND_SELECT_PROMPT: &str = "Select what you want to forward";
+const WORKLOAD_KIND_SELECT_PROMPT: &str = "Select which kind of workload you want to connect with";
+const WORKLOAD_SELECT_PROMPT: &str = "Select which workload you want to connect with";
 const POD_SELECT_PROMPT: &str = "Select which pod you want to connect with";
+const READY_POD_OPTION: &str = "Any ready pod (follow the workload)";
+const SERVICE_SELECT_PROMPT: &str = "Select which service you want to connect with";
 const CONTAINER_SELECT_PROMPT: &str = "Select which container you want to port forward";
-const SELECTED_PORT: &str = "Select which port to expose";
-const USER_PORT: &str = "Input which port you want to use";
+const SELECTED_PORT: &str = "Select which ports to expose";
+const USER_PORT: &str = "Input which local port you want to use for the port";
+const USER_PORT_HELP: &str = "Use 0 or auto to get the same free port on every session";
+const UNAVAILABLE_PORT: &str = "The port is already used, choose another port or use auto";
+const EMPTY_SELECTED_PORT: &str = "Select at least one port";
+const CUSTOM_PORT_OPTION: &str = "Enter a custom port";
+const CUSTOM_PORT_PROMPT: &str = "Input the ports of the pod you want to expose";
+const CUSTOM_PORT_HELP: &str = "Separate several ports with a comma (e.g: 6060, 8081)";
+const MODE_SELECT_PROMPT: &str = "Select how the traffic should be forwarded";
+
+// Kind of resource which can be forwarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetKind {
+    Pod,
+    Workload,
+    Service
+}
+
+impl fmt::Display for TargetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetKind::Pod => write!(f, "pod"),
+            TargetKind::Workload => write!(f, "workload (deployment, statefulset...)"),
+            TargetKind::Service => write!(f, "service")
+        }
+    }
+}
+
+// Port proposed when selecting the ports to expose. The ports which are not declared by the container
+// can be entered manually
+#[derive(Debug, Clone)]
+enum PortChoice {
+    Declared(DeclaredPort),
+    Suggested(SuggestedPort),
+    Custom
+}
+
+impl fmt::Display for PortChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortChoice::Declared(port) => write!(f, "{port}"),
+            PortChoice::Suggested(port) => write!(f, "{port}"),
+            PortChoice::Custom => write!(f, "{CUSTOM_PORT_OPTION}")
+        }
+    }
+}
+
+// Pod proposed when selecting a pod of a workload
+#[derive(Debug, Clone)]
+enum PodChoice {
+    AnyReady,
+    Pod(PodOption)
+}
+
+impl fmt::Display for PodChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PodChoice::AnyReady => write!(f, "{READY_POD_OPTION}"),
+            PodChoice::Pod(pod) => write!(f, "{pod}")
+        }
+    }
+}
+
+// Values provided with the CLI which skip the matching prompts. The forward waits for a ready pod
+// up to the wait timeout when it's provided
+#[derive(Debug, Default)]
+pub struct ForwardArgs {
+    pub addresses: Vec<IpAddr>,
+    pub wait: Option<Duration>,
+    pub health: Option<String>,
+    pub mode: Option<Mode>,
+    pub ports: Vec<PortArg>,
+    pub pod: Option<String>,
+    pub container: Option<String>,
+    pub selector: Option<String>,
+    pub workload: Option<WorkloadRef>,
+    pub service: Option<String>
+}
+
+impl ForwardArgs {
+    /// Check whether the resource to forward has been provided
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn has_target(&self) -> bool {
+        self.pod.is_some()
+            || self.selector.is_some()
+            || self.workload.is_some()
+            || self.service.is_some()
+    }
+}
 
 /// Trigger Scenario
-///     List a set of pod and with the provided context and exec into it
+///     Ask which kind of resource the user want to forward and build the target to forward.
+///     The target is saved in the history
+///
+/// # Arguments
+/// * `context` - Option<String>
+/// * `ns` - String
+/// * `args` - ForwardArgs
+pub async fn trigger_scenario(context: Option<String>, ns: String, args: ForwardArgs) -> Result<Target, KubeErr> {
+    let kind = match (&args.service, &args.workload, args.selector.is_some() || args.pod.is_some()) {
+        (Some(_), _, _) => TargetKind::Service,
+        (_, Some(_), _) => TargetKind::Workload,
+        (_, _, true) => TargetKind::Pod,
+        _ => utils::value_or_prompt(None, "<RESOURCE>", || {
+            let kinds = vec![TargetKind::Pod, TargetKind::Workload, TargetKind::Service];
+            Ok(Select::new(KIND_SELECT_PROMPT, kinds).prompt()?)
+        })?
+    };
+
+    let target = match kind {
+        TargetKind::Pod => trigger_pod_scenario(context, ns, args).await?,
+        TargetKind::Workload => trigger_workload_scenario(context, ns, args).await?,
+        TargetKind::Service => trigger_service_scenario(context, ns, args).await?
+    };
+
+    history::save_target(&target);
+
+    Ok(target)
+}
+
+/// List a set of pod with the provided context and build the target to forward
 ///
 /// # Arguments
 /// * `context` - Option<String>
 /// * `ns` - String
-pub async fn trigger_scenario(context: Option<String>, ns: String) -> Result<(), KubeErr> {
-    let mut pod_list = PodsList::new(context, &ns).await?;
+/// * `args` - ForwardArgs
+async fn trigger_pod_scenario(context: Option<String>, ns: String, args: ForwardArgs) -> Result<Target, KubeErr> {
+    let pod_list = PodsList::new(context.clone(), &ns, &args.selector).await?;
     let pod_list_name = pod_list.get_pod_name_list();
 
-    if pod_list_name.is_empty() {
+    // the pod may be created later when waiting for the pod provided with the CLI
+    if pod_list_name.is_empty() && (args.wait.is_none() || !args.has_target()) {
+        return Err(KubeErr::EmptyPods(ns))
+    }
+
+    // a ready pod is automatically selected when following a selector
+    let (selected_pod, pod_list) = match &args.selector {
+        Some(selector) => {
+            let (pod, pod_list) = get_ready_pod(&context, &ns, pod_list, &args.selector, &[], args.wait, selector).await?;
+            log::info!("Selected the pod {pod} matching `{selector}`");
+            (pod, pod_list)
+        },
+        None => {
+            let pod = utils::value_or_prompt(args.pod.clone(), "--pod", || {
+                Ok(Select::new(POD_SELECT_PROMPT, pod_list.get_pod_options()).prompt()?.name)
+            })?;
+
+            let pod_list = ensure_pod_ready(&context, &ns, pod_list, &pod, &[], args.wait).await?;
+            (pod, pod_list)
+        }
+    };
+
+    let selector = args.selector.clone();
+    build_pod_target(context, ns, pod_list, selected_pod, selector, None, args)
+}
+
+/// List a set of workload and the pods owned by the selected workload and build the target to forward.
+/// When any ready pod is selected, the forward follows the pods of the workload
+///
+/// # Arguments
+/// * `context` - Option<String>
+/// * `ns` - String
+/// * `args` - ForwardArgs
+async fn trigger_workload_scenario(context: Option<String>, ns: String, args: ForwardArgs) -> Result<Target, KubeErr> {
+    let client = authenticate_with_cluster(&context).await?;
+    let from_args = args.workload.is_some();
+    let workload = match args.workload.clone() {
+        Some(workload) => workload,
+        None => {
+            let kind = Select::new(WORKLOAD_KIND_SELECT_PROMPT, WORKLOAD_KINDS.to_vec()).prompt()?;
+            let names = workload::get_workload_name_list(client.clone(), &ns, kind).await?;
+            if names.is_empty() {
+                return Err(KubeErr::EmptyWorkloads(kind.to_string(), ns));
+            }
+
+            let name = Select::new(WORKLOAD_SELECT_PROMPT, names).prompt()?;
+            WorkloadRef { kind, name }
+        }
+    };
+
+    // a deployment is followed through its replicasets up to its pods
+    let resolved = workload.resolve(client, &ns).await?;
+    let selector = Some(resolved.selector.clone());
+    let pod_list = load_pods(&context, &ns, &selector, &resolved.owners).await?;
+    if pod_list.get_pod_name_list().is_empty() && args.wait.is_none() {
         return Err(KubeErr::EmptyPods(ns))
     }
 
-    // prompt the selection of the pod for the user
-    let selected_pod = Select::new(POD_SELECT_PROMPT, pod_list_name)
-        .prompt()?;
+    // a workload provided with the CLI use any ready pod
+    let selected = match from_args {
+        true => PodChoice::AnyReady,
+        false => {
+            let mut options = vec![PodChoice::AnyReady];
+            options.extend(pod_list.get_pod_options().into_iter().map(PodChoice::Pod));
+
+            Select::new(POD_SELECT_PROMPT, options).prompt()?
+        }
+    };
+
+    let target = workload.to_string();
+    let (selected_pod, selector, pod_list) = match selected {
+        PodChoice::AnyReady => {
+            let (pod, pod_list) = get_ready_pod(&context, &ns, pod_list, &selector, &resolved.owners, args.wait, &target).await?;
+            log::info!("Selected the pod {pod} of the {workload}");
+            (pod, selector, pod_list)
+        },
+        PodChoice::Pod(pod) => {
+            let pod_list = ensure_pod_ready(&context, &ns, pod_list, &pod.name, &resolved.owners, args.wait).await?;
+            (pod.name, None, pod_list)
+        }
+    };
+
+    build_pod_target(context, ns, pod_list, selected_pod, selector, Some(workload), args)
+}
+
+/// List the pods matching the selector. Only the pods owned by one of the owners are kept when owners are provided
+///
+/// # Arguments
+/// * `context` - &Option<String>
+/// * `ns` - &str
+/// * `selector` - &Option<String>
+/// * `owners` - &[String]
+async fn load_pods(context: &Option<String>, ns: &str, selector: &Option<String>, owners: &[String]) -> Result<PodsList, KubeErr> {
+    let mut pod_list = PodsList::new(context.clone(), ns, selector).await?;
+    if !owners.is_empty() {
+        pod_list.filter_owned_by(owners);
+    }
+
+    Ok(pod_list)
+}
+
+/// Get a ready pod of the list. When none is ready and waiting is enabled, the pods matching the selector
+/// and owned by the owners are watched until one is ready. The pods are then listed again
+///
+/// # Arguments
+/// * `context` - &Option<String>
+/// * `ns` - &str
+/// * `pod_list` - PodsList
+/// * `selector` - &Option<String>
+/// * `owners` - &[String]
+/// * `wait` - Option<Duration>
+/// * `target` - &str
+async fn get_ready_pod(
+    context: &Option<String>,
+    ns: &str,
+    pod_list: PodsList,
+    selector: &Option<String>,
+    owners: &[String],
+    wait: Option<Duration>,
+    target: &str
+) -> Result<(String, PodsList), KubeErr> {
+    if let Some(pod) = pod_list.get_ready_pod_name() {
+        return Ok((pod, pod_list));
+    }
+
+    let timeout = wait.ok_or_else(|| KubeErr::NoReadyPod(target.to_owned()))?;
+    let client = authenticate_with_cluster(context).await?;
+    let api: Api<Pod> = Api::namespaced(client, ns);
+
+    let mut params = ListParams::default();
+    if let Some(selector) = selector {
+        params = params.labels(selector);
+    }
+
+    let filter = |pod: &Pod| owners.is_empty() || is_owned_by(pod.owner_references(), owners);
+    let pod = follow::wait_ready_pod(api, params, filter, timeout, target).await?;
 
+    Ok((pod, load_pods(context, ns, selector, owners).await?))
+}
+
+/// Check that the selected pod can be forwarded. When the pod is not ready or doesn't exist yet and waiting
+/// is enabled, the pod is watched until it's ready. Otherwise a pod which is running but not ready is forwarded
+/// with a warning and a pod which doesn't exist is an error
+///
+/// # Arguments
+/// * `context` - &Option<String>
+/// * `ns` - &str
+/// * `pod_list` - PodsList
+/// * `pod` - &str
+/// * `owners` - &[String]
+/// * `wait` - Option<Duration>
+async fn ensure_pod_ready(
+    context: &Option<String>,
+    ns: &str,
+    pod_list: PodsList,
+    pod: &str,
+    owners: &[String],
+    wait: Option<Duration>
+) -> Result<PodsList, KubeErr> {
+    let state = pod_list.get_pod_state(pod);
+
+    match (state, wait) {
+        (Some(state), _) if state.ready => Ok(pod_list),
+        (_, Some(timeout)) => {
+            let client = authenticate_with_cluster(context).await?;
+            let api: Api<Pod> = Api::namespaced(client, ns);
+            let params = ListParams::default().fields(&format!("metadata.name={pod}"));
+            follow::wait_ready_pod(api, params, |_| true, timeout, &format!("pod/{pod}")).await?;
+
+            load_pods(context, ns, &None, owners).await
+        },
+        (Some(state), None) if state.is_running() => {
+            log::warn!("The pod {pod} is not ready ({state}), the forward may fail");
+            Ok(pod_list)
+        },
+        (Some(state), None) => Err(KubeErr::PodNotRunning(pod.to_owned(), state.to_string())),
+        (None, None) => Err(KubeErr::PodNotFound(pod.to_owned(), ns.to_owned()))
+    }
+}
+
+/// Select the container and the ports of the selected pod and build the target to forward
+///
+/// # Arguments
+/// * `context` - Option<String>
+/// * `ns` - String
+/// * `pod_list` - PodsList
+/// * `selected_pod` - String
+/// * `selector` - Option<String>
+/// * `workload` - Option<WorkloadRef>
+/// * `args` - ForwardArgs
+fn build_pod_target(
+    context: Option<String>,
+    ns: String,
+    mut pod_list: PodsList,
+    selected_pod: String,
+    selector: Option<String>,
+    workload: Option<WorkloadRef>,
+    args: ForwardArgs
+) -> Result<Target, KubeErr> {
     // set the selected pod on the pod_list
     pod_list.set_selected_pod(selected_pod);
 
@@ -37,21 +363,282 @@ pub async fn trigger_scenario(context: Option<String>, ns: String) -> Result<(),
         return Err(KubeErr::EmptyContainers)
     }
 
-    // propose a set of command to the user
-    let selected_container = Select::new(CONTAINER_SELECT_PROMPT, containers_name)
-        .prompt()?;
+    // a single container is used when the terminal is not interactive
+    let selected_container = match (args.container.clone(), containers_name.len()) {
+        (Some(container), _) => container,
+        (None, 1) if !utils::is_interactive() => containers_name[0].clone(),
+        (None, _) => utils::value_or_prompt(None, "--container", || {
+            Ok(Select::new(CONTAINER_SELECT_PROMPT, containers_name).prompt()?)
+        })?
+    };
+
+    // get a list of port for the selected container along with the ports it likely listens on
+    let declared = pod_list
+        .get_port_for_container(selected_container.clone())
+        .unwrap_or_default();
+
+    // ports provided with the CLI skip the selection of the ports
+    let ports = match args.ports.is_empty() {
+        false => args.ports,
+        true => {
+            let suggested = pod_list.get_suggested_ports(selected_container.clone());
+            prompt_ports(declared.clone(), suggested, true)?
+        }
+    };
+
+    let mut mappings = Vec::new();
+    for port in ports {
+        let remote = port.resolve(|name| pod_list.get_port_by_name(name))?;
+        check_protocol(&declared, remote)?;
+
+        let default_mode = pod_list.get_mode_for_port(selected_container.clone(), remote as i32);
+        mappings.push(prompt_mapping(port.local, remote, remote, args.mode, default_mode, &args.addresses)?);
+    }
+
+    let pod = pod_list
+        .get_selected_pod_name()
+        .ok_or(KubeErr::SelectedPod)?;
+
+    Ok(Target {
+        context,
+        namespace: ns,
+        pod,
+        owner: pod_list.get_selected_pod_owner(),
+        selector,
+        workload_ref: workload,
+        container: Some(selected_container),
+        mappings,
+        health: args.health
+    })
+}
+
+/// List a set of service with the provided context and build the target to forward. The service port
+/// is mapped to its target port on a ready pod backing the service. When no pod is ready and waiting
+/// is enabled, the pods matching the selector of the service are watched until one is ready
+///
+/// # Arguments
+/// * `context` - Option<String>
+/// * `ns` - String
+/// * `args` - ForwardArgs
+async fn trigger_service_scenario(context: Option<String>, ns: String, args: ForwardArgs) -> Result<Target, KubeErr> {
+    let client = authenticate_with_cluster(&context).await?;
+    let name = match args.service {
+        Some(name) => name,
+        None => {
+            let names = service::get_service_name_list(client.clone(), &ns).await?;
+            if names.is_empty() {
+                return Err(KubeErr::EmptyServices(ns));
+            }
+
+            Select::new(SERVICE_SELECT_PROMPT, names).prompt()?
+        }
+    };
+
+    let service = ServiceWrapper::new(client.clone(), &ns, &name).await?;
+    let selector = service.get_selector();
+    let target = format!("service/{name}");
+    let pod = match (service::get_ready_endpoint_pod(client.clone(), &ns, &name).await?, args.wait, &selector) {
+        (Some(pod), _, _) => pod,
+        (None, Some(timeout), Some(selector)) => {
+            let api: Api<Pod> = Api::namespaced(client, &ns);
+            let params = ListParams::default().labels(selector);
+            follow::wait_ready_pod(api, params, |_| true, timeout, &target).await?
+        },
+        _ => return Err(KubeErr::NoReadyPod(target))
+    };
 
-    // get a list of port for the selected container
-    let ports = pod_list.get_port_for_container(selected_container);
-    if ports.is_none() {
-        return Err(KubeErr::EmptyPorts);
+    log::info!("Selected the pod {pod} backing the service {name}");
+
+    // the pod is used to resolve the named target ports of the service
+    let mut pod_list = PodsList::new(context.clone(), &ns, &selector).await?;
+    pod_list.set_selected_pod(pod.clone());
+
+    let declared = service.get_ports();
+    let ports = match args.ports.is_empty() {
+        false => args.ports,
+        true => {
+            if declared.is_empty() {
+                return Err(KubeErr::EmptyPorts);
+            }
+
+            prompt_ports(declared.clone(), Vec::new(), false)?
+        }
+    };
+
+    let mut mappings = Vec::new();
+    for port in ports {
+        let service_port = port.resolve(|name| service.get_port_by_name(name))?;
+        check_protocol(&declared, service_port)?;
+
+        let target_port = match service.get_target_port(service_port as i32) {
+            Some(IntOrString::Int(target_port)) => target_port,
+            Some(IntOrString::String(name)) => pod_list
+                .get_port_by_name(&name)
+                .ok_or(KubeErr::WrongServicePort(service_port))?,
+            None => return Err(KubeErr::WrongServicePort(service_port))
+        };
+
+        let default_mode = service.get_mode_for_port(service_port as i32);
+        mappings.push(prompt_mapping(port.local, service_port, target_port as u16, args.mode, default_mode, &args.addresses)?);
     }
 
-    let ports = ports.unwrap();
-    let selected_port = Select::new(SELECTED_PORT, ports).prompt()?;
-    let user_port: u16 = Text::new(USER_PORT)
-        .prompt()?
-        .parse::<u16>()?;
+    Ok(Target {
+        context,
+        namespace: ns,
+        pod,
+        owner: pod_list.get_selected_pod_owner(),
+        selector,
+        workload_ref: None,
+        container: None,
+        mappings,
+        health: args.health
+    })
+}
 
-    pod_list.expose_pod(selected_port as u16, user_port).await
+/// Prompt the selection of the ports to expose. The suggested ports and a custom port are proposed
+/// after the ports declared by the container. The UDP and SCTP ports are skipped as the port-forward
+/// only supports TCP
+///
+/// # Arguments
+/// * `declared` - Vec<DeclaredPort>
+/// * `suggested` - Vec<SuggestedPort>
+/// * `custom` - bool
+fn prompt_ports(declared: Vec<DeclaredPort>, suggested: Vec<SuggestedPort>, custom: bool) -> Result<Vec<PortArg>, KubeErr> {
+    let (declared, skipped): (Vec<_>, Vec<_>) = declared
+        .into_iter()
+        .partition(DeclaredPort::is_tcp);
+
+    for port in skipped {
+        log::warn!("The port {port} is skipped as the port-forward only supports TCP");
+    }
+
+    let mut options: Vec<_> = declared
+        .into_iter()
+        .map(PortChoice::Declared)
+        .chain(suggested.into_iter().map(PortChoice::Suggested))
+        .collect();
+
+    if custom {
+        options.push(PortChoice::Custom);
+    }
+
+    let selected = utils::value_or_prompt(None, "--port", || {
+        Ok(MultiSelect::new(SELECTED_PORT, options)
+            .with_validator(&|selected| match selected.is_empty() {
+                true => Err(EMPTY_SELECTED_PORT.to_owned()),
+                false => Ok(())
+            })
+            .prompt()?)
+    })?;
+
+    let mut ports = Vec::new();
+    for choice in selected {
+        match choice {
+            PortChoice::Declared(port) => ports.push(port.port as u16),
+            PortChoice::Suggested(port) => ports.push(port.port as u16),
+            PortChoice::Custom => {
+                let input = Text::new(CUSTOM_PORT_PROMPT)
+                    .with_help_message(CUSTOM_PORT_HELP)
+                    .with_validator(&|input| parse_ports(input).map(|_| ()).map_err(|err| err.to_string()))
+                    .prompt()?;
+
+                ports.extend(parse_ports(&input)?);
+            }
+        }
+    }
+
+    let ports = ports
+        .into_iter()
+        .map(|port| PortArg { remote: RemotePort::Number(port), local: None })
+        .collect();
+
+    Ok(ports)
+}
+
+/// Check that a port can be forwarded. A port which is only declared with the UDP or the SCTP protocol
+/// can't be forwarded while a port which is not declared is assumed to use TCP
+///
+/// # Arguments
+/// * `declared` - &[DeclaredPort]
+/// * `port` - u16
+fn check_protocol(declared: &[DeclaredPort], port: u16) -> Result<(), KubeErr> {
+    let matching: Vec<_> = declared
+        .iter()
+        .filter(|p| p.port == port as i32)
+        .collect();
+
+    match matching.iter().find(|p| !p.is_tcp()) {
+        Some(p) if !matching.iter().any(|p| p.is_tcp()) => Err(KubeErr::UnsupportedProtocol(port, p.protocol.to_uppercase())),
+        _ => Ok(())
+    }
+}
+
+/// Build the mapping of a port. The local port and the mode are prompted when they're not provided.
+/// When the terminal is not interactive, the selected port and the guessed mode are used
+///
+/// # Arguments
+/// * `local` - Option<u16>
+/// * `port` - u16
+/// * `remote` - u16
+/// * `mode` - Option<Mode>
+/// * `default_mode` - Mode
+/// * `addresses` - &[IpAddr]
+fn prompt_mapping(
+    local: Option<u16>,
+    port: u16,
+    remote: u16,
+    mode: Option<Mode>,
+    default_mode: Mode,
+    addresses: &[IpAddr]
+) -> Result<PortMapping, KubeErr> {
+    let local = match local {
+        Some(local) => local,
+        None if !utils::is_interactive() => port,
+        None => {
+            let default = port.to_string();
+            let input = Text::new(&format!("{USER_PORT} {port}"))
+                .with_default(&default)
+                .with_help_message(USER_PORT_HELP)
+                .with_validator(&|input| match parse_local_port(input) {
+                    Ok(local) if is_port_available(addresses, local) => Ok(()),
+                    Ok(_) => Err(UNAVAILABLE_PORT.to_owned()),
+                    Err(err) => Err(err.to_string())
+                })
+                .prompt()?;
+
+            parse_local_port(&input)?
+        }
+    };
+
+    // the mode proposed by default depends on whether the port looks like an http port
+    let mode = match mode {
+        Some(mode) => mode,
+        None if !utils::is_interactive() => default_mode,
+        None => {
+            let modes = vec![Mode::Http, Mode::Tcp];
+            let cursor = modes.iter().position(|m| *m == default_mode).unwrap_or_default();
+
+            Select::new(MODE_SELECT_PROMPT, modes)
+                .with_starting_cursor(cursor)
+                .prompt()?
+        }
+    };
+
+    Ok(PortMapping { remote, local, mode })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn expect_to_not_find_unknown_pod() {
+        let err = ensure_pod_ready(&None, "default", PodsList::from(vec![]), "api-1", &[], None)
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(err.kind(), "pod_not_found");
+        assert!(err.to_string().contains("api-1"));
+    }
 }
@@ -0,0 +1,24 @@
+use inquire::Select;
+use crate::{
+    error::KubeErr,
+    history::{self, History},
+    session::target::Target
+};
+
+// Constant
+const HISTORY_SELECT_PROMPT: &str = "Select which forward you want to replay";
+
+/// Trigger Scenario
+///     Ask which forward of the history the user want to replay and build the target to forward
+pub async fn trigger_scenario() -> Result<Target, KubeErr> {
+    let entries = History::load()?.entries();
+    if entries.is_empty() {
+        return Err(KubeErr::EmptyHistory);
+    }
+
+    let entry = Select::new(HISTORY_SELECT_PROMPT, entries).prompt()?;
+    let target = entry.into_target().await?;
+    history::save_target(&target);
+
+    Ok(target)
+}
@@ -1,3 +1,5 @@
 pub mod context;
 pub mod forward;
 pub mod namespace;
+pub mod session;
+pub mod history;
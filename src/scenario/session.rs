@@ -0,0 +1,14 @@
+use inquire::Confirm;
+use crate::error::KubeErr;
+
+// Constant
+const ADD_TARGET_PROMPT: &str = "Do you want to forward another pod?";
+
+/// Run the scenario to ask whether the user want to add another target to the session
+pub fn trigger_scenario() -> Result<bool, KubeErr> {
+    let add = Confirm::new(ADD_TARGET_PROMPT)
+        .with_default(false)
+        .prompt()?;
+
+    Ok(add)
+}
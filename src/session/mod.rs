@@ -0,0 +1,153 @@
+use std::{net::IpAddr, sync::Arc};
+use futures::future::join_all;
+use tokio_util::sync::CancellationToken;
+use target::Target;
+use crate::{
+    kube::stats::Stats,
+    events::{self, Event, ErrorInfo},
+    env::{self, EnvFile},
+    registry::{self, PortRange}
+};
+
+pub mod target;
+
+// Constant
+const CTRL_C_MSG: &str = "Use Ctrl-C / Cmd-C to stop the server";
+
+// Set of targets which are forwarded concurrently on the same local addresses
+#[derive(Debug)]
+pub struct Session {
+    targets: Vec<Target>,
+    addresses: Arc<Vec<IpAddr>>,
+    port_range: PortRange,
+    env_file: Option<EnvFile>
+}
+
+impl Session {
+    /// Create a new Session
+    ///
+    /// # Arguments
+    /// * `addresses` - Vec<IpAddr>
+    /// * `port_range` - PortRange
+    pub fn new(addresses: Vec<IpAddr>, port_range: PortRange) -> Session {
+        Session {
+            targets: Vec::new(),
+            addresses: Arc::new(addresses),
+            port_range,
+            env_file: None
+        }
+    }
+
+    /// Add a target to forward within the session. The auto ports of the target are assigned
+    /// from the port range
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `target` - Target
+    pub fn add_target(&mut self, mut target: Target) -> &mut Self {
+        registry::assign_ports(&mut target, self.port_range, &self.addresses);
+        self.targets.push(target);
+        self
+    }
+
+    /// Get the addresses used by the listeners
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn addresses(&self) -> Vec<IpAddr> {
+        self.addresses.to_vec()
+    }
+
+    /// Get the range used to assign the auto ports
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn port_range(&self) -> PortRange {
+        self.port_range
+    }
+
+    /// Set the file where the forwarded endpoints are written
+    ///
+    /// # Arguments
+    /// * `&mut self` - Self
+    /// * `env_file` - Option<EnvFile>
+    pub fn set_env_file(&mut self, env_file: Option<EnvFile>) -> &mut Self {
+        self.env_file = env_file;
+        self
+    }
+
+    /// Get the file where the forwarded endpoints are written
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn env_file(&self) -> Option<EnvFile> {
+        self.env_file.clone()
+    }
+
+    /// Split the session into its targets and the addresses used by the listeners
+    ///
+    /// # Arguments
+    /// * `self` - Self
+    pub fn into_parts(self) -> (Vec<Target>, Vec<IpAddr>) {
+        (self.targets, self.addresses.to_vec())
+    }
+
+    /// Run every target of the session concurrently until a Ctrl-C. A target which fails does not stop
+    /// the others. The env file is kept up to date while the targets are forwarded and removed afterwards
+    ///
+    /// # Arguments
+    /// * `self` - Self
+    pub async fn run(self) {
+        let shutdown = CancellationToken::new();
+        let signal = shutdown.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                signal.cancel();
+            }
+        });
+
+        let stats: Vec<_> = self.targets
+            .iter()
+            .map(|target| target.new_stats())
+            .collect();
+
+        let watcher = self.env_file
+            .clone()
+            .map(|file| tokio::spawn(env::watch(file, self.targets.clone(), stats.clone(), self.addresses[0])));
+
+        let tasks = self.targets
+            .into_iter()
+            .zip(stats)
+            .map(|(target, stats)| tokio::spawn(supervise(target, stats, self.addresses.clone(), shutdown.clone())));
+
+        log::info!("{CTRL_C_MSG}");
+        join_all(tasks).await;
+
+        if let Some(watcher) = watcher {
+            watcher.abort();
+        }
+
+        if let Some(file) = &self.env_file {
+            file.remove();
+        }
+    }
+}
+
+/// Forward a target and log the status of the forward
+///
+/// # Arguments
+/// * `target` - Target
+/// * `stats` - Vec<Arc<Stats>>
+/// * `addresses` - Arc<Vec<IpAddr>>
+/// * `shutdown` - CancellationToken
+async fn supervise(target: Target, stats: Vec<Arc<Stats>>, addresses: Arc<Vec<IpAddr>>, shutdown: CancellationToken) {
+    log::info!("[{target}] starting forward");
+
+    match target.expose(&addresses, &stats, shutdown).await {
+        Ok(_) => log::info!("[{target}] forward stopped"),
+        Err(err) => {
+            log::error!("[{target}] forward failed: {err}");
+            events::emit(Event::ForwardFailed { target: target.to_string(), error: ErrorInfo::from(&err) });
+        }
+    }
+}
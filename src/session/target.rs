@@ -0,0 +1,202 @@
+use std::{fmt, net::IpAddr, sync::Arc};
+use kube::Api;
+use k8s_openapi::api::core::v1::Pod;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use serde::{Serialize, Deserialize};
+use crate::kube::{
+    CURRENT_CONTEXT,
+    authenticate_with_cluster,
+    follow::{follow_pods, OwnerFilter},
+    listener::Context,
+    port::PortMapping,
+    pod::PodOwner,
+    stats::Stats,
+    workload::{REPLICA_SET, WorkloadRef}
+};
+use crate::error::KubeErr;
+
+// A pod to forward along with the cluster where it's located. When a selector is provided
+// the forward follows the pods matching the selector, and owned by the workload when the pod has been
+// selected through a workload. The container and the owner of the pod are kept to replay the forward
+// later on. The health path is requested by the probe of the HTTP ports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    pub context: Option<String>,
+    pub namespace: String,
+    pub pod: String,
+    pub owner: Option<PodOwner>,
+    pub selector: Option<String>,
+    #[serde(default)]
+    pub workload_ref: Option<WorkloadRef>,
+    pub container: Option<String>,
+    pub mappings: Vec<PortMapping>,
+    #[serde(default)]
+    pub health: Option<String>
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let context = self.context.as_deref().unwrap_or(CURRENT_CONTEXT);
+        match &self.selector {
+            Some(selector) => write!(f, "{context}/{}/{selector}", self.namespace),
+            None => write!(f, "{context}/{}/{}", self.namespace, self.pod)
+        }
+    }
+}
+
+impl Target {
+    /// Get the workload of the target (e.g: deployment/api). The workload of a pod owned by a ReplicaSet
+    /// is the Deployment as the name of the ReplicaSet changes on every rollout
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn workload(&self) -> String {
+        match (&self.selector, &self.owner) {
+            (Some(selector), _) => selector.to_owned(),
+            (None, Some(owner)) if owner.kind == REPLICA_SET => {
+                let name = owner.name
+                    .rsplit_once('-')
+                    .map(|(name, _)| name)
+                    .unwrap_or(&owner.name);
+
+                format!("deployment/{name}")
+            },
+            (None, Some(owner)) => format!("{}/{}", owner.kind.to_lowercase(), owner.name),
+            (None, None) => format!("pod/{}", self.pod)
+        }
+    }
+
+    /// Get a short name of the target. It is the name of the workload or the value of the first
+    /// label of the selector (e.g: api for app=api)
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn name(&self) -> String {
+        let workload = self.workload();
+        let name = workload.rsplit('/').next().unwrap_or_default();
+        let label = name.split(',').next().unwrap_or_default();
+
+        label.rsplit('=').next().unwrap_or_default().trim().to_owned()
+    }
+
+    /// Create the statistics of each port mapping of the target
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    pub fn new_stats(&self) -> Vec<Arc<Stats>> {
+        self.mappings
+            .iter()
+            .map(|_| Arc::new(Stats::default()))
+            .collect()
+    }
+
+    /// Expose the pod based on the container ports and the given user ports. A listener is created
+    /// for each port mapping. Implementation is highly inspired by the link below
+    /// @link https://github.com/kube-rs/kube-rs/blob/master/examples/pod_portforward_bind.rs
+    ///
+    /// # Arguments
+    /// * `&self` - Self
+    /// * `addresses` - &[IpAddr]
+    /// * `stats` - &[Arc<Stats>]
+    /// * `shutdown` - CancellationToken
+    pub async fn expose(&self, addresses: &[IpAddr], stats: &[Arc<Stats>], shutdown: CancellationToken) -> Result<(), KubeErr> {
+        let client = authenticate_with_cluster(&self.context).await?;
+        let pod_api: Api<Pod> = Api::namespaced(client.clone(), &self.namespace);
+
+        // the watcher update the pod used by the listeners when the pod is replaced. It runs within
+        // the forward so that it's dropped along with the listeners
+        let (tx, rx) = watch::channel(self.pod.clone());
+        let watcher = async {
+            if let Some(selector) = self.selector.clone() {
+                let filter = self.workload_ref
+                    .clone()
+                    .map(|workload| OwnerFilter::new(client, &self.namespace, workload));
+
+                follow_pods(pod_api.clone(), selector, filter, tx).await;
+            }
+
+            // the listeners keep the last pod when the watcher stops
+            futures::future::pending::<()>().await
+        };
+
+        let listeners = self.mappings
+            .iter()
+            .zip(stats)
+            .map(|(mapping, stats)| {
+                let context = Context::new(pod_api.clone(), rx.clone(), mapping.remote, stats.clone(), shutdown.clone());
+                async move {
+                    context
+                        .port_forward_local(addresses, mapping.local, mapping.mode, self.health.as_deref())
+                        .await
+                }
+            });
+
+        tokio::select! {
+            res = futures::future::try_join_all(listeners) => res.map(|_| ()),
+            _ = watcher => Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expect_to_display_target_with_current_context() {
+        let target = Target {
+            context: None,
+            namespace: "default".to_owned(),
+            pod: "foo".to_owned(),
+            owner: None,
+            selector: None,
+            workload_ref: None,
+            container: None,
+            mappings: vec![],
+            health: None
+        };
+
+        assert_eq!(target.to_string(), "current/default/foo");
+    }
+
+    #[test]
+    fn expect_to_display_target_with_selector() {
+        let target = Target {
+            context: Some("prod".to_owned()),
+            namespace: "default".to_owned(),
+            pod: "api-1".to_owned(),
+            owner: None,
+            selector: Some("app=api".to_owned()),
+            workload_ref: None,
+            container: None,
+            mappings: vec![],
+            health: None
+        };
+
+        assert_eq!(target.to_string(), "prod/default/app=api");
+        assert_eq!(target.name(), "api");
+    }
+
+    #[test]
+    fn expect_to_get_workload_of_replica_set() {
+        let target = Target {
+            context: None,
+            namespace: "default".to_owned(),
+            pod: "api-5d4f-abcde".to_owned(),
+            owner: Some(PodOwner {
+                kind: "ReplicaSet".to_owned(),
+                name: "api-5d4f".to_owned(),
+                uid: "1234".to_owned()
+            }),
+            selector: None,
+            workload_ref: None,
+            container: None,
+            mappings: vec![],
+            health: None
+        };
+
+        assert_eq!(target.workload(), "deployment/api");
+        assert_eq!(target.name(), "api");
+    }
+}
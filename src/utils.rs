@@ -1,5 +1,5 @@
-use std::sync::Arc;
-use tokio::{sync::Mutex, io::AsyncRead, io::AsyncWrite};
+use std::{sync::Arc, io::IsTerminal, time::Duration};
+use tokio::{sync::Mutex, io::AsyncRead, io::AsyncWrite, net::TcpStream};
 use std::convert::Infallible;
 use tower::util::ServiceExt;
 use kube::api::Portforwarder;
@@ -7,17 +7,127 @@ use hyper::{
     Request,
     Body,
     Response,
-    client::conn::Connection,
+    StatusCode,
+    header,
+    client::conn::{Connection, SendRequest},
     body::HttpBody
 };
+use crate::{
+    daemon::logger,
+    error::KubeErr,
+    kube::{listener::Context, remote::RemoteError, stats::{CountingStream, Stats}},
+    events::{self, Event, ErrorInfo}
+};
+
+// Constant
+// The delay is fixed and bounds both the wait for the stream and the response of the pod
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
 
+// HTTP connection with the pod shared by the requests of a local connection along with the error
+// reported by the pod. The connection is opened by the first request
+pub type Link = Arc<Mutex<Option<(SendRequest<Body>, RemoteError)>>>;
+
+/// Forward the request to the pod. A failure is returned to the local client as a 502 response
+/// or a 504 response when the pod does not answer in time
+///
+/// # Arguments
+/// * `context` - Context
+/// * `link` - Link
+/// * `req` - Request<Body>
 pub async fn handle(
-    context: Arc<Mutex<hyper::client::conn::SendRequest<hyper::Body>>>,
+    context: Context,
+    link: Link,
     req: Request<Body>,
 ) -> Result<Response<Body>, Infallible> {
-    let mut sender = context.lock().await;
-    let response = sender.ready().await.unwrap().send_request(req).await.unwrap();
-    Ok(response)
+    let (pod, port) = (context.pod_name(), context.pod_port());
+    let err = match send_request(&context, link, req).await {
+        Ok(response) => return Ok(response),
+        Err(err) => err
+    };
+
+    let status = get_error_status(&err);
+    log::error!("The request to the pod {pod}:{port} failed with {status} ({}): {err}", err.kind());
+    context.stats().push_error(err.to_string());
+    events::emit(Event::RequestFailed { pod, port, status: status.as_u16(), error: ErrorInfo::from(&err) });
+
+    Ok(error_response(status, &err))
+}
+
+/// Send the request over the stream of the connection. The stream is opened on the first request
+/// and reopened when it has been closed (e.g: the port-forward broke) or when it did not answer in time.
+/// The error reported by the pod is preferred over the error of the connection
+///
+/// # Arguments
+/// * `context` - &Context
+/// * `link` - Link
+/// * `req` - Request<Body>
+async fn send_request(
+    context: &Context,
+    link: Link,
+    req: Request<Body>
+) -> Result<Response<Body>, KubeErr> {
+    let mut link = link.lock().await;
+    if let Some((sender, _)) = link.as_mut() {
+        match tokio::time::timeout(RESPONSE_TIMEOUT, sender.ready()).await {
+            Ok(Ok(_)) => {},
+            Ok(Err(_)) => {
+                log::warn!("The stream with the pod has been closed, reopening a new stream");
+                *link = None;
+            },
+            Err(_) => {
+                log::warn!("The stream with the pod is not responding, reopening a new stream");
+                *link = None;
+            }
+        }
+    }
+
+    let (sender, remote) = match link.as_mut() {
+        Some(link) => link,
+        None => link.insert(context.open_sender().await?)
+    };
+
+    let res = match tokio::time::timeout(RESPONSE_TIMEOUT, sender.ready()).await {
+        Ok(Ok(_)) => tokio::time::timeout(RESPONSE_TIMEOUT, sender.send_request(req))
+            .await
+            .map_err(|_| KubeErr::ResponseTimeout(RESPONSE_TIMEOUT.as_secs()))
+            .and_then(|res| res.map_err(|err| KubeErr::Upstream(err.to_string()))),
+        Ok(Err(err)) => Err(KubeErr::StreamClosed(err.to_string())),
+        Err(_) => Err(KubeErr::ResponseTimeout(RESPONSE_TIMEOUT.as_secs()))
+    };
+
+    // the stream is still busy with the request which timed out, a new stream is opened by the next request
+    match res {
+        Ok(response) => Ok(response),
+        Err(KubeErr::ResponseTimeout(secs)) => {
+            *link = None;
+            Err(KubeErr::ResponseTimeout(secs))
+        },
+        Err(err) => Err(remote.get().await.unwrap_or(err))
+    }
+}
+
+/// Get the status of the response returned to the local client when the request failed
+///
+/// # Arguments
+/// * `err` - &KubeErr
+fn get_error_status(err: &KubeErr) -> StatusCode {
+    match err {
+        KubeErr::ResponseTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::BAD_GATEWAY
+    }
+}
+
+/// Build the response explaining why the request failed
+///
+/// # Arguments
+/// * `status` - StatusCode
+/// * `err` - &KubeErr
+fn error_response(status: StatusCode, err: &KubeErr) -> Response<Body> {
+    let mut response = Response::new(Body::from(format!("kubeforward: {err}\n")));
+    *response.status_mut() = status;
+    response.headers_mut().insert(header::CONTENT_TYPE, header::HeaderValue::from_static("text/plain; charset=utf-8"));
+
+    response
 }
 
 /// Listen to the connection error in a tokio task. The signature come from the hyper crate
@@ -31,7 +141,7 @@ pub fn listen_conn_error<T, B>(conn: Connection<T, B>) where
     B::Error: Into<Box<dyn std::error::Error + Send + Sync>>
 {
     // listen to error with the connection
-    tokio::spawn(async move {
+    logger::spawn(async move {
         if let Err(e) = conn.await {
             log::error!("error in connection: {}", e);
         }
@@ -42,10 +152,124 @@ pub fn listen_conn_error<T, B>(conn: Connection<T, B>) where
 ///
 /// # Arguments
 /// * `f` - PortForwarder
-pub fn listen_forwarder_error(f: Portforwarder) {
-    tokio::spawn(async move {
+/// * `pod` - String
+/// * `port` - u16
+pub fn listen_forwarder_error(f: Portforwarder, pod: String, port: u16) {
+    logger::spawn(async move {
         if let Err(e) = f.join().await {
-            log::error!("forwarder error {}", e);
+            log::error!("forwarder error {}, a new stream will be opened for the next request", e);
+            let error = ErrorInfo::from(&KubeErr::Network(e.to_string()));
+            events::emit(Event::ForwarderError { pod, port, error });
         }
     });
 }
+
+/// Copy the bytes of a local connection both ways over a dedicated port-forward stream. The bytes are
+/// counted as they flow. The number of bytes transferred is returned unless the pod reported an error
+/// for the stream
+///
+/// # Arguments
+/// * `mut socket` - TcpStream
+/// * `upstream` - S
+/// * `forwarder` - Portforwarder
+/// * `mut remote` - RemoteError
+/// * `stats` - Arc<Stats>
+pub async fn forward_connection<S>(
+    mut socket: TcpStream,
+    upstream: S,
+    forwarder: Portforwarder,
+    mut remote: RemoteError,
+    stats: Arc<Stats>
+) -> Result<u64, KubeErr> where
+    S: AsyncRead + AsyncWrite + Unpin
+{
+    let mut upstream = CountingStream::new(upstream, stats);
+    let res = tokio::io::copy_bidirectional(&mut socket, &mut upstream).await;
+
+    // the error of a refused connection may be reported once the stream is closed without any data
+    let err = match &res {
+        Ok((_, received)) if *received > 0 => remote.try_get(),
+        _ => remote.get().await
+    };
+
+    if let Some(err) = err {
+        return Err(err);
+    }
+
+    let (sent, received) = res?;
+
+    // dropping the stream close the websocket of the forwarder
+    drop(upstream);
+    forwarder
+        .join()
+        .await
+        .map_err(|err| KubeErr::Network(err.to_string()))?;
+
+    Ok(sent + received)
+}
+
+/// Check whether the prompts can be used (stdin is a terminal)
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+/// Use the value provided by the CLI or prompt it when the terminal is interactive
+///
+/// # Arguments
+/// * `value` - Option<T>
+/// * `arg` - &str
+/// * `prompt` - F
+pub fn value_or_prompt<T, F>(value: Option<T>, arg: &str, prompt: F) -> Result<T, KubeErr> where
+    F: FnOnce() -> Result<T, KubeErr>
+{
+    get_value(value, arg, is_interactive(), prompt)
+}
+
+/// Use the value provided by the CLI or prompt it when the prompts can be used. A missing
+/// argument is returned otherwise
+///
+/// # Arguments
+/// * `value` - Option<T>
+/// * `arg` - &str
+/// * `interactive` - bool
+/// * `prompt` - F
+fn get_value<T, F>(value: Option<T>, arg: &str, interactive: bool, prompt: F) -> Result<T, KubeErr> where
+    F: FnOnce() -> Result<T, KubeErr>
+{
+    match (value, interactive) {
+        (Some(value), _) => Ok(value),
+        (None, true) => prompt(),
+        (None, false) => Err(KubeErr::MissingArg(arg.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expect_to_require_missing_arg_when_not_interactive() {
+        let err = get_value(None::<String>, "--pod", false, || Ok("api-1".to_owned())).unwrap_err();
+
+        assert_eq!(err.kind(), "missing_arg");
+        assert!(err.to_string().contains("--pod"));
+        assert_eq!(get_value(Some("api-2".to_owned()), "--pod", false, || Ok("api-1".to_owned())).unwrap(), "api-2");
+        assert_eq!(get_value(None, "--pod", true, || Ok("api-1".to_owned())).unwrap(), "api-1");
+    }
+
+    #[test]
+    fn expect_to_get_error_status() {
+        assert_eq!(get_error_status(&KubeErr::ResponseTimeout(60)), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(get_error_status(&KubeErr::StreamClosed("closed".to_owned())), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn expect_to_build_error_response() {
+        let err = KubeErr::Upstream("connection refused".to_owned());
+        let response = error_response(StatusCode::BAD_GATEWAY, &err);
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, format!("kubeforward: {err}\n"));
+    }
+}
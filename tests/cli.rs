@@ -0,0 +1,80 @@
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Output, Stdio}
+};
+
+/// Run kubeforward without a terminal and without a cluster. The home and the working directory
+/// are set to a directory dedicated to the test so that the configuration of the machine is not used
+///
+/// # Arguments
+/// * `name` - &str
+/// * `files` - &[(&str, &str)]
+/// * `args` - &[&str]
+fn run_kubeforward(name: &str, files: &[(&str, &str)], args: &[&str]) -> Output {
+    let dir = get_temp_dir(name);
+    for (file, content) in files {
+        fs::write(dir.join(file), content).unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kubeforward"))
+        .args(args)
+        .current_dir(&dir)
+        .env("HOME", &dir)
+        .env("XDG_DATA_HOME", &dir)
+        .env("KUBECONFIG", dir.join("missing-kubeconfig"))
+        .stdin(Stdio::null())
+        .output()
+        .expect("Expect to run kubeforward");
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    output
+}
+
+/// Create an empty directory dedicated to a test
+///
+/// # Arguments
+/// * `name` - &str
+fn get_temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kubeforward-cli-{}-{name}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+#[test]
+fn expect_to_fail_on_missing_arg_without_terminal() {
+    let output = run_kubeforward("missing-arg", &[], &["--namespace", "default"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("argument is required"));
+}
+
+#[test]
+fn expect_to_forward_with_a_wrong_configuration() {
+    let files = [(".kubeforward.toml", "port_range = ")];
+    let output = run_kubeforward("wrong-config", &files, &["--namespace", "default"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("the default port range is used"));
+    assert!(stdout.contains("argument is required"));
+}
+
+#[test]
+fn expect_to_not_run_the_dashboard_with_the_json_output() {
+    let output = run_kubeforward("tui-json", &[], &["--tui", "--output", "json"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr.contains("JSON output"));
+}
+
+#[test]
+fn expect_to_fail_exec_when_the_forwards_fail_to_start() {
+    let args = ["exec", "--context", "missing", "--namespace", "default", "--pod", "api-1", "-p", "8080", "--", "true"];
+    let output = run_kubeforward("exec-failure", &[], &args);
+
+    assert_eq!(output.status.code(), Some(125));
+}